@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use athena::jobs;
+use redis::aio::MultiplexedConnection;
+use tokio::sync::watch;
+
+/// How often delayed jobs are checked for being due.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How many due jobs are moved at once, so that a large backlog doesn't block
+/// Redis for too long.
+const BATCH_SIZE: usize = 100;
+
+/// Pushes delayed jobs onto their queue as they become due, until shutdown.
+pub(crate) async fn run_mover(mut con: MultiplexedConnection, mut shutdown: watch::Receiver<bool>) {
+    while !*shutdown.borrow() {
+        match jobs::move_due_jobs(&mut con, BATCH_SIZE).await {
+            Ok((moved, dropped)) => {
+                if dropped > 0 {
+                    eprintln!("Dropped {} delayed jobs for unknown queues", dropped);
+                }
+
+                // There might be more due jobs waiting, so check again right away.
+                if moved + dropped >= BATCH_SIZE {
+                    continue;
+                }
+            }
+            Err(e) => eprintln!("Error moving delayed jobs: {}", e)
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            _ = shutdown.wait_for(|stopping| *stopping) => break
+        }
+    }
+}
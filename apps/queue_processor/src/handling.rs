@@ -1,19 +1,50 @@
-use redis::AsyncCommands;
+use athena::jobs::{self, Job as _};
+use crate::delayed;
+use redis::{aio::MultiplexedConnection, AsyncCommands, Direction};
+use tokio::{sync::watch, task::JoinHandle};
 use tokio_cron_scheduler::{Job, JobScheduler};
-use std::{collections::HashMap, sync::Arc, error::Error};
+use std::{collections::HashMap, sync::Arc, error::Error, time::Duration};
 use async_trait::async_trait;
 
 #[async_trait]
 pub trait QueueHandler: Send + Sync {
-    /// Return the queue name this handler is responsible for.
-    fn queue_name(&self) -> &'static str;
+    /// The job this handler processes, which also decides its queue.
+    type Job: jobs::Job;
+
+    /// How many messages of the queue may be handled at the same time.
+    fn concurrency(&self) -> usize {
+        1
+    }
+
+    /// Process a job asynchronously.
+    async fn handle(&self, job: Self::Job) -> Result<(), Box<dyn Error>>;
+}
+
+/// The type-erased side of [`QueueHandler`], so that handlers of different
+/// jobs can live in the same registry.
+#[async_trait]
+trait MessageHandler: Send + Sync {
+    fn concurrency(&self) -> usize;
 
-    /// Process a message asynchronously.
     async fn handle_message(&self, message: String) -> Result<(), Box<dyn Error>>;
 }
 
+#[async_trait]
+impl<H: QueueHandler> MessageHandler for H {
+    fn concurrency(&self) -> usize {
+        QueueHandler::concurrency(self)
+    }
+
+    async fn handle_message(&self, message: String) -> Result<(), Box<dyn Error>> {
+        self.handle(jobs::decode(&message)?).await
+    }
+}
+
 #[async_trait]
 pub trait ScheduledTask: Send + Sync {
+    /// A unique name for the task, used to report when it last ran.
+    fn name(&self) -> &'static str;
+
     /// Returns the cron expression that defines when the task should run.
     fn schedule(&self) -> &'static str;
 
@@ -23,7 +54,16 @@ pub trait ScheduledTask: Send + Sync {
 
 pub struct HandlerRegistry {
     // Key is the queue name, value is the handler.
-    handlers: HashMap<String, Arc<dyn QueueHandler>>,
+    handlers: HashMap<String, Arc<dyn MessageHandler>>,
+    // Flipped to `true` once the workers should stop taking new messages.
+    shutdown: watch::Sender<bool>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Default for HandlerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl HandlerRegistry {
@@ -31,59 +71,217 @@ impl HandlerRegistry {
     pub fn new() -> Self {
         Self {
             handlers: HashMap::new(),
+            shutdown: watch::Sender::new(false),
+            workers: Vec::new(),
         }
     }
 
     /// Register a handler for a given queue.
     pub fn register_handler<H: QueueHandler + 'static>(&mut self, handler: H) {
         self.handlers
-            .insert(handler.queue_name().to_string(), Arc::new(handler));
+            .insert(H::Job::QUEUE.to_string(), Arc::new(handler));
     }
 
     /// Start a listener task for each registered handler.
     ///
-    /// Each task will connect to Redis, and continuously poll its assigned queue
-    /// (using a blocking pop with timeout) to process new messages.
-    pub async fn start_all(&self, redis_url: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// Messages are moved into a processing list while they are handled, and
+    /// only removed from it once handled successfully, so that none are lost
+    /// if the process dies mid-way. Failed messages are scheduled as delayed
+    /// jobs to be retried with an exponential backoff, and moved to
+    /// `<queue>:dead` once they run out of attempts.
+    ///
+    /// Waiting for messages blocks a connection, so each worker gets its own
+    /// for that, while everything else goes through a shared one. Otherwise
+    /// workers would wait on each other, whatever their concurrency.
+    ///
+    /// Messages left in processing lists by a previous run are put back into
+    /// their queue first, which assumes a single processor per queue.
+    ///
+    /// Delayed jobs are moved onto their queue by a separate task once due,
+    /// over a connection of its own.
+    pub async fn start_all(&mut self, redis_url: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let client = redis::Client::open(redis_url)?;
+        let mut con = client.get_multiplexed_async_connection().await?;
+
+        for queue_name in self.handlers.keys() {
+            let recovered = recover_orphans(&mut con, queue_name).await?;
+
+            if recovered > 0 {
+                println!("Recovered {} orphaned messages in '{}'", recovered, queue_name);
+            }
+        }
+
         for (queue_name, handler) in self.handlers.iter() {
-            let redis_url = redis_url.to_string();
-            let queue = queue_name.clone();
-            let handler = Arc::clone(handler);
-
-            // Spawn a task for each queue handler.
-            tokio::spawn(async move {
-                // Create a new Redis client and async connection.
-                let client = redis::Client::open(redis_url.as_str())
-                    .expect("Failed to create Redis client");
-
-                let mut con = client
-                    .get_multiplexed_async_connection()
-                    .await
-                    .expect("Failed to get Redis connection");
-
-                loop {
-                    // Use BRPOP with a timeout (e.g., 5 seconds).
-                    let result: Option<(String, String)> = match con.brpop(queue.as_str(), 5f64).await {
-                        Ok(res) => res,
-                        Err(e) => {
-                            eprintln!("Error on BRPOP for queue {}: {}", queue, e);
-                            continue;
-                        }
-                    };
-
-                    if let Some((_queue, message)) = result {
-                        if let Err(e) = handler.handle_message(message).await {
-                            eprintln!("Error handling message in {}: {}", queue, e);
-                        }
-                    } else {
-                        // Timeout reached – you could check for shutdown signals here.
-                        println!("No message on '{}' queue, retrying...", queue);
-                    }
-                }
-            });
+            // Spawn as many workers as the handler allows for each queue.
+            for _ in 0..handler.concurrency().max(1) {
+                self.workers.push(tokio::spawn(run_worker(
+                    con.clone(),
+                    client.get_multiplexed_async_connection().await?,
+                    queue_name.clone(),
+                    Arc::clone(handler),
+                    self.shutdown.subscribe()
+                )));
+            }
         }
+
+        self.workers.push(tokio::spawn(delayed::run_mover(
+            client.get_multiplexed_async_connection().await?,
+            self.shutdown.subscribe()
+        )));
         Ok(())
     }
+
+    /// Stops every worker from taking new messages, and waits for the
+    /// messages being handled to finish for at most `timeout`.
+    ///
+    /// Messages still being handled are left in their processing list, and get
+    /// picked up again on the next start.
+    pub async fn shutdown(self, timeout: Duration) -> bool {
+        self.shutdown.send_replace(true);
+
+        let workers = self.workers;
+        let finished = tokio::time::timeout(timeout, async move {
+            for worker in workers {
+                let _ = worker.await;
+            }
+        }).await;
+
+        finished.is_ok()
+    }
+}
+
+/// How many times a message is handled before it's considered dead.
+const MAX_ATTEMPTS: i64 = 5;
+
+/// The delay before the first retry, doubled on every following one.
+const BASE_BACKOFF: chrono::Duration = chrono::Duration::minutes(10);
+
+/// How long to wait before retrying a message that failed `attempts` times.
+fn retry_delay(attempts: i64) -> chrono::Duration {
+    BASE_BACKOFF * 2i32.pow(attempts.max(1) as u32 - 1)
+}
+
+async fn run_worker(
+    mut con: MultiplexedConnection,
+    mut blocking: MultiplexedConnection,
+    queue: String,
+    handler: Arc<dyn MessageHandler>,
+    mut shutdown: watch::Receiver<bool>
+) {
+    let processing = processing_list(&queue);
+
+    while !*shutdown.borrow() {
+        // Waiting is cancelled as soon as shutdown starts. Should the BLMOVE
+        // still have moved a message, it stays in the processing list and is
+        // recovered on the next start.
+        let result = tokio::select! {
+            result = blocking.blmove::<_, _, Option<String>>(queue.as_str(), processing.as_str(), Direction::Right, Direction::Left, 5f64) => result,
+            _ = shutdown.wait_for(|stopping| *stopping) => break
+        };
+
+        let result = match result {
+            Ok(res) => res,
+            Err(e) => {
+                eprintln!("Error on BLMOVE for queue {}: {}", queue, e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+
+        let Some(message) = result else {
+            continue;
+        };
+
+        if let Err(e) = process_message(&mut con, &queue, handler.as_ref(), message).await {
+            eprintln!("Error acknowledging message in {}: {}", queue, e);
+        }
+    }
+}
+
+fn processing_list(queue: &str) -> String {
+    format!("{}:processing", queue)
+}
+
+fn attempts_key(queue: &str) -> String {
+    format!("{}:attempts", queue)
+}
+
+/// Handles a message that was moved into the processing list. Failures are
+/// retried later on as a delayed job, until the message runs out of attempts.
+async fn process_message(
+    con: &mut MultiplexedConnection,
+    queue: &str,
+    handler: &dyn MessageHandler,
+    message: String
+) -> redis::RedisResult<()> {
+    let processing = processing_list(queue);
+    let attempts_key = attempts_key(queue);
+    let metrics = jobs::metrics_key(queue);
+    // Messages queued before they carried an ID are counted by their content.
+    let message_id = jobs::message_id(&message).unwrap_or_else(|| message.clone());
+
+    // The error isn't `Send`, so it can't be held across an await.
+    let error = match handler.handle_message(message.clone()).await {
+        Ok(()) => None,
+        Err(e) => Some(e.to_string())
+    };
+
+    let Some(error) = error else {
+        redis::pipe()
+            .atomic()
+            .lrem(processing.as_str(), 1, message.as_str())
+            .hdel(attempts_key.as_str(), message_id.as_str())
+            .hincr(metrics.as_str(), jobs::counters::PROCESSED, 1)
+            .query_async::<()>(con)
+            .await?;
+
+        return Ok(());
+    };
+
+    // Attempts are kept in Redis so that they survive restarts.
+    let attempts: i64 = con.hincr(attempts_key.as_str(), message_id.as_str(), 1).await?;
+
+    if attempts >= MAX_ATTEMPTS {
+        eprintln!("Error handling message in {} (attempt {}, giving up): {}", queue, attempts, error);
+
+        redis::pipe()
+            .atomic()
+            .lpush(jobs::dead_letter_list(queue), message.as_str())
+            .lrem(processing.as_str(), 1, message.as_str())
+            .hdel(attempts_key.as_str(), message_id.as_str())
+            .hincr(metrics.as_str(), jobs::counters::FAILED, 1)
+            .query_async::<()>(con)
+            .await?;
+
+        return Ok(());
+    }
+
+    eprintln!("Error handling message in {} (attempt {}): {}", queue, attempts, error);
+
+    // The retry is scheduled before the message leaves the processing list.
+    // Should the process die in between, it's handled twice rather than lost.
+    let due_at = chrono::Utc::now() + retry_delay(attempts);
+    jobs::schedule_message_async(con, queue, &message, due_at).await?;
+
+    redis::pipe()
+        .atomic()
+        .lrem(processing.as_str(), 1, message.as_str())
+        .hincr(metrics.as_str(), jobs::counters::RETRIED, 1)
+        .query_async::<()>(con)
+        .await
+}
+
+/// Puts messages left in the processing list back at the front of the queue,
+/// oldest first.
+async fn recover_orphans(con: &mut MultiplexedConnection, queue: &str) -> redis::RedisResult<usize> {
+    let processing = processing_list(queue);
+    let mut recovered = 0;
+
+    while con.lmove::<_, _, Option<String>>(processing.as_str(), queue, Direction::Left, Direction::Right).await?.is_some() {
+        recovered += 1;
+    }
+
+    Ok(recovered)
 }
 
 pub struct TaskManager {
@@ -91,6 +289,12 @@ pub struct TaskManager {
 }
 
 
+impl Default for TaskManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TaskManager {
     /// Creates a new TaskManager.
     pub fn new() -> Self {
@@ -104,20 +308,40 @@ impl TaskManager {
         self.scheduled_tasks.push(Arc::new(task));
     }
 
-    /// Starts both the queue handlers and the scheduled tasks.
-    pub async fn start(self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Starts the scheduled tasks, returning the scheduler running them so
+    /// that it can be shut down. The time each task last ran, and last ran
+    /// successfully, is recorded in Redis.
+    pub async fn start(self, redis_url: &str) -> Result<JobScheduler, Box<dyn std::error::Error>> {
+        let client = redis::Client::open(redis_url)?;
+        let con = client.get_multiplexed_async_connection().await?;
+
         // Initialize the cron scheduler.
         let scheduler = JobScheduler::new().await?;
         
         for task in self.scheduled_tasks {
             let cron_expr = task.schedule();
             let task_clone = Arc::clone(&task);
+            let con = con.clone();
 
             let job = Job::new_async(cron_expr, move |_uuid, _l| {
                 let task_clone = Arc::clone(&task_clone);
+                let mut con = con.clone();
                 Box::pin(async move {
-                    if let Err(e) = task_clone.execute().await {
-                        eprintln!("Error executing scheduled task: {}", e);
+                    // The error isn't `Send`, so it can't be held across an await.
+                    let result = task_clone.execute().await.map_err(|e| e.to_string());
+                    let finished_at = chrono::Utc::now().timestamp();
+                    let mut pipe = redis::pipe();
+
+                    pipe.hset(jobs::TASK_RUNS, task_clone.name(), finished_at);
+
+                    if let Err(e) = result {
+                        eprintln!("Error executing scheduled task {}: {}", task_clone.name(), e);
+                    } else {
+                        pipe.hset(jobs::TASK_SUCCESSES, task_clone.name(), finished_at);
+                    }
+
+                    if let Err(e) = pipe.query_async::<()>(&mut con).await {
+                        eprintln!("Error recording the run of task {}: {}", task_clone.name(), e);
                     }
                 })
             })?;
@@ -126,6 +350,18 @@ impl TaskManager {
         }
 
         scheduler.start().await?;
-        Ok(())
+        Ok(scheduler)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_back_off_exponentially() {
+        let delays: Vec<i64> = (1..MAX_ATTEMPTS).map(|attempts| retry_delay(attempts).num_minutes()).collect();
+
+        assert_eq!(delays, vec![10, 20, 40, 80]);
     }
-}
\ No newline at end of file
+}
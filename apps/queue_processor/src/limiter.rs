@@ -0,0 +1,100 @@
+use std::{sync::Mutex, time::{Duration, Instant}};
+
+/// A token bucket shared by everything that talks to a rate limited service.
+pub struct RateLimiter {
+    capacity: f64,
+    refill_rate: f64,
+    state: Mutex<BucketState>
+}
+
+struct BucketState {
+    tokens: f64,
+    updated_at: Instant
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing bursts of up to `burst` requests, while
+    /// never going above `per_minute` requests in any given minute. The bucket
+    /// refills slower than `per_minute` to make up for the burst.
+    pub fn new(burst: u32, per_minute: u32) -> Self {
+        let capacity = burst.clamp(1, per_minute.max(1)) as f64;
+        let refill_rate = (per_minute as f64 - capacity).max(1.0) / 60.0;
+
+        Self {
+            capacity,
+            refill_rate,
+            state: Mutex::new(BucketState { tokens: capacity, updated_at: Instant::now() })
+        }
+    }
+
+    /// Waits until a request can be made, taking a token from the bucket.
+    pub async fn acquire(&self) {
+        while let Err(wait) = self.try_acquire(Instant::now()) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Refills the bucket up to `now` and takes a token from it, or returns
+    /// how long to wait until the next token is available.
+    fn try_acquire(&self, now: Instant) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+
+        state.tokens = (state.tokens + now.saturating_duration_since(state.updated_at).as_secs_f64() * self.refill_rate).min(self.capacity);
+        state.updated_at = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            return Ok(());
+        }
+
+        Err(Duration::from_secs_f64((1.0 - state.tokens) / self.refill_rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bursts_are_allowed_up_to_the_capacity() {
+        let limiter = RateLimiter::new(5, 60);
+        let now = Instant::now();
+
+        for _ in 0..5 {
+            assert!(limiter.try_acquire(now).is_ok());
+        }
+
+        assert!(limiter.try_acquire(now).is_err());
+    }
+
+    #[test]
+    fn tokens_refill_at_the_remaining_rate() {
+        // 55 tokens a minute are left after the burst of 5, so one every 60/55 seconds.
+        let limiter = RateLimiter::new(5, 60);
+        let now = Instant::now();
+
+        for _ in 0..5 {
+            limiter.try_acquire(now).unwrap();
+        }
+
+        let wait = limiter.try_acquire(now).unwrap_err();
+        assert!((wait.as_secs_f64() - 60.0 / 55.0).abs() < 1e-6);
+
+        assert!(limiter.try_acquire(now + wait / 2).is_err());
+        let refilled_at = now + wait + Duration::from_millis(1);
+        assert!(limiter.try_acquire(refilled_at).is_ok());
+        assert!(limiter.try_acquire(refilled_at).is_err());
+    }
+
+    #[test]
+    fn refilling_stops_at_the_capacity() {
+        let limiter = RateLimiter::new(5, 60);
+        let later = Instant::now() + Duration::from_secs(3600);
+
+        for _ in 0..5 {
+            assert!(limiter.try_acquire(later).is_ok());
+        }
+
+        assert!(limiter.try_acquire(later).is_err());
+    }
+}
@@ -1,26 +1,93 @@
-use std::env;
+use std::{env, sync::Arc, time::Duration};
 use dotenvy::dotenv;
 use handling::{HandlerRegistry, TaskManager};
+use limiter::RateLimiter;
+use rosu_v2::Osu;
+use sea_orm::Database;
 
 pub mod queues;
 pub mod tasks;
 pub mod handling;
+pub mod delayed;
+pub mod limiter;
+
+/// How long handlers get to finish their current message when shutting down,
+/// kept below the grace period Docker gives before killing the container.
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(8);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().expect("A proper environmental file has not been found");
     
     let mut queue_registry = HandlerRegistry::new();
-    let task_registry = TaskManager::new();
+    let mut task_registry = TaskManager::new();
     let redis_url = env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+    let db_pool = Database::connect(env::var("DATABASE_URL").expect("DATABASE_URL must be set"))
+        .await
+        .expect("Failed to connect to database");
+    let osu = Arc::new(
+        Osu::builder()
+            .url(env::var("OSU_URL").expect("OSU_URL must be set"))
+            .client_id(env::var("OSU_CLIENT_ID").expect("OSU_CLIENT_ID must be set").parse().expect("OSU_CLIENT_ID must be a number"))
+            .client_secret(env::var("OSU_CLIENT_SECRET").expect("OSU_CLIENT_SECRET must be set"))
+            .build()
+            .await
+            .expect("Failed to create osu! API client")
+    );
+    // Shared by every handler calling the osu! API, which asks for no more
+    // than 60 requests per minute.
+    let osu_limiter = Arc::new(RateLimiter::new(5, 60));
 
-    queue_registry.register_handler(queues::users::UserUpdateQueueHandler);
+    queue_registry.register_handler(queues::users::UserUpdateQueueHandler {
+        osu: osu.clone(),
+        limiter: osu_limiter.clone(),
+        db_pool: db_pool.clone(),
+        redis: redis::Client::open(redis_url.as_str())?
+    });
+    queue_registry.register_handler(queues::beatmapsets::BeatmapsetUpdateQueueHandler { osu: osu.clone(), limiter: osu_limiter.clone(), db_pool: db_pool.clone() });
     queue_registry.start_all(&redis_url).await?;
 
-    //task_registry
-    task_registry.start().await?;
+    task_registry.register_scheduled_task(tasks::sessions::PurgeExpiredSessionsTask { db_pool: db_pool.clone() });
+    task_registry.register_scheduled_task(tasks::beatmapsets::EnqueueStaleBeatmapsetsTask {
+        db_pool: db_pool.clone(),
+        redis: redis::Client::open(redis_url.as_str())?,
+        stale_after: chrono::Duration::hours(env::var("BEATMAPSET_REFRESH_HOURS").ok().and_then(|hours| hours.parse().ok()).unwrap_or(24))
+    });
+    let mut scheduler = task_registry.start(&redis_url).await?;
+
+    wait_for_shutdown_signal().await;
+    println!("Shutting down, waiting for in-flight messages...");
+
+    let timeout = env::var("SHUTDOWN_TIMEOUT_SECONDS")
+        .ok()
+        .and_then(|seconds| seconds.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT);
+
+    if !queue_registry.shutdown(timeout).await {
+        eprintln!("Some messages were still being handled after {:?}, they will be retried on the next start", timeout);
+    }
+
+    scheduler.shutdown().await?;
+
+    Ok(())
+}
+
+/// Resolves once the process receives either SIGTERM or SIGINT.
+#[cfg(unix)]
+async fn wait_for_shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
 
-    loop {
-        tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    let mut terminate = signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM");
+
+    tokio::select! {
+        _ = terminate.recv() => {}
+        _ = tokio::signal::ctrl_c() => {}
     }
+}
+
+/// Resolves once the process receives Ctrl+C, the only signal available.
+#[cfg(not(unix))]
+async fn wait_for_shutdown_signal() {
+    tokio::signal::ctrl_c().await.expect("Failed to listen for Ctrl+C");
 }
\ No newline at end of file
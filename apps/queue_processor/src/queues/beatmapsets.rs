@@ -0,0 +1,46 @@
+use std::{error::Error, sync::Arc};
+
+use athena::{jobs::BeatmapsetJob, prelude::beatmaps::FullBeatmapset};
+use rosu_v2::{error::OsuError, Osu};
+use sea_orm::DatabaseConnection;
+
+use crate::{handling::QueueHandler, limiter::RateLimiter};
+
+pub(crate) struct BeatmapsetUpdateQueueHandler {
+    pub osu: Arc<Osu>,
+    pub limiter: Arc<RateLimiter>,
+    pub db_pool: DatabaseConnection
+}
+
+#[async_trait::async_trait]
+impl QueueHandler for BeatmapsetUpdateQueueHandler {
+    type Job = BeatmapsetJob;
+
+    fn concurrency(&self) -> usize {
+        4
+    }
+
+    async fn handle(&self, job: BeatmapsetJob) -> Result<(), Box<dyn Error>> {
+        let BeatmapsetJob::Refresh { beatmapset_ids } = job;
+
+        for beatmapset_id in beatmapset_ids {
+            self.limiter.acquire().await;
+
+            match self.osu.beatmapset(beatmapset_id as u32).await {
+                Ok(beatmapset) => {
+                    // Mappers the API doesn't return are stored as restricted.
+                    self.limiter.acquire().await;
+                    let creators = self.osu.users(FullBeatmapset::creator_ids(&beatmapset)).await?;
+
+                    FullBeatmapset::update_from_osu(&beatmapset, &creators, &self.db_pool).await?;
+                }
+                Err(OsuError::NotFound) => {
+                    FullBeatmapset::mark_deleted(beatmapset_id, &self.db_pool).await?;
+                }
+                Err(error) => return Err(error.into())
+            }
+        }
+
+        Ok(())
+    }
+}
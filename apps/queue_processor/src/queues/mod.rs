@@ -1 +1,2 @@
+pub mod beatmapsets;
 pub mod users;
\ No newline at end of file
@@ -1,17 +1,49 @@
-use std::error::Error;
+use std::{error::Error, sync::Arc};
 
-use crate::handling::QueueHandler;
+use athena::{jobs::{self, UserJob}, prelude::users::FullUser};
+use redis::AsyncCommands;
+use rosu_v2::{error::OsuError, Osu};
+use sea_orm::DatabaseConnection;
 
-pub(crate) struct UserUpdateQueueHandler;
+use crate::{handling::QueueHandler, limiter::RateLimiter};
+
+pub(crate) struct UserUpdateQueueHandler {
+    pub osu: Arc<Osu>,
+    pub limiter: Arc<RateLimiter>,
+    pub db_pool: DatabaseConnection,
+    pub redis: redis::Client
+}
 
 #[async_trait::async_trait]
 impl QueueHandler for UserUpdateQueueHandler {
-    fn queue_name(&self) -> &'static str {
-        "loved:queues:user_update"
+    type Job = UserJob;
+
+    fn concurrency(&self) -> usize {
+        4
     }
 
-    async fn handle_message(&self, _message: String) -> Result<(), Box<dyn Error>> {
-        // TODO: Implement message handling logic
+    async fn handle(&self, job: UserJob) -> Result<(), Box<dyn Error>> {
+        let UserJob::Update { user_ids } = job;
+        let mut con = self.redis.get_multiplexed_async_connection().await?;
+
+        for user_id in user_ids {
+            self.limiter.acquire().await;
+
+            match self.osu.user(user_id as u32).await {
+                Ok(user) => {
+                    FullUser::upsert_from_osu(&user, &self.db_pool).await?;
+                }
+                // Restricted and deleted users aren't returned anymore
+                Err(OsuError::NotFound) => {
+                    FullUser::mark_unavailable(user_id, &self.db_pool).await?;
+                }
+                Err(error) => return Err(error.into())
+            }
+
+            // The server can queue the user again from now on.
+            con.del::<_, ()>(jobs::user_update_pending_key(user_id)).await?;
+        }
+
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,46 @@
+use std::error::Error;
+
+use athena::{jobs::{self, BeatmapsetJob}, prelude::beatmaps::FullBeatmapset};
+use chrono::{Duration, Utc};
+use sea_orm::DatabaseConnection;
+
+use crate::handling::ScheduledTask;
+
+/// How many beatmapsets go into a single queue message.
+const CHUNK_SIZE: usize = 50;
+
+pub(crate) struct EnqueueStaleBeatmapsetsTask {
+    pub db_pool: DatabaseConnection,
+    pub redis: redis::Client,
+    /// How old the data of a beatmapset has to be for it to be refreshed.
+    pub stale_after: Duration
+}
+
+#[async_trait::async_trait]
+impl ScheduledTask for EnqueueStaleBeatmapsetsTask {
+    fn name(&self) -> &'static str {
+        "enqueue_stale_beatmapsets"
+    }
+
+    fn schedule(&self) -> &'static str {
+        // Every hour, at half past
+        "0 30 * * * *"
+    }
+
+    async fn execute(&self) -> Result<(), Box<dyn Error>> {
+        let beatmapset_ids = FullBeatmapset::find_stale_submitted(Utc::now().naive_utc() - self.stale_after, &self.db_pool).await?;
+
+        if beatmapset_ids.is_empty() {
+            return Ok(());
+        }
+
+        let mut con = self.redis.get_multiplexed_async_connection().await?;
+
+        for chunk in beatmapset_ids.chunks(CHUNK_SIZE) {
+            jobs::enqueue_async(&mut con, &BeatmapsetJob::Refresh { beatmapset_ids: chunk.to_vec() }).await?;
+        }
+
+        println!("Queued {} stale beatmapsets for an update", beatmapset_ids.len());
+        Ok(())
+    }
+}
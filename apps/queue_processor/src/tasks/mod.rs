@@ -0,0 +1,2 @@
+pub mod beatmapsets;
+pub mod sessions;
\ No newline at end of file
@@ -0,0 +1,29 @@
+use std::error::Error;
+
+use athena::prelude::sessions::FullSession;
+use sea_orm::DatabaseConnection;
+
+use crate::handling::ScheduledTask;
+
+pub(crate) struct PurgeExpiredSessionsTask {
+    pub db_pool: DatabaseConnection
+}
+
+#[async_trait::async_trait]
+impl ScheduledTask for PurgeExpiredSessionsTask {
+    fn name(&self) -> &'static str {
+        "purge_expired_sessions"
+    }
+
+    fn schedule(&self) -> &'static str {
+        // Every hour, on the hour
+        "0 0 * * * *"
+    }
+
+    async fn execute(&self) -> Result<(), Box<dyn Error>> {
+        let purged = FullSession::purge_expired(&self.db_pool).await?;
+
+        println!("Purged {} expired sessions", purged);
+        Ok(())
+    }
+}
@@ -35,6 +35,114 @@ pub enum LovedError {
     #[error(StatusCode::BAD_REQUEST, "ERR_INVALID_PAGE", "The provided page number is invalid.")]
     InvalidPage,
 
+    /// `400 BAD REQUEST`
+    /// An error that occurs because the client sent a malformed cursor, or
+    /// used a cursor on an endpoint that doesn't support them.
+    #[error(StatusCode::BAD_REQUEST, "ERR_INVALID_CURSOR", "The provided cursor is invalid.")]
+    InvalidCursor,
+
+    /// FILTERING ///
+
+    /// `400 BAD REQUEST`
+    /// An error that occurs because the client sent a filter, sort or search
+    /// parameter with a value that could not be understood.
+    #[error(StatusCode::BAD_REQUEST, "ERR_INVALID_QUERY_PARAMETER", "The provided value for '{parameter}' is invalid.")]
+    InvalidQueryParameter {
+        parameter: &'static str
+    },
+
+    /// SUBMISSIONS ///
+
+    /// `400 BAD REQUEST`
+    /// An error that occurs because the client tried to submit a beatmapset
+    /// that has already been ranked or loved.
+    #[error(StatusCode::BAD_REQUEST, "ERR_BEATMAPSET_INELIGIBLE", "The provided beatmapset is already ranked or loved.")]
+    BeatmapsetIneligible,
+
+    /// `400 BAD REQUEST`
+    /// An error that occurs because the client sent a game mode that doesn't
+    /// exist, or that the beatmapset has no difficulties for.
+    #[error(StatusCode::BAD_REQUEST, "ERR_INVALID_GAME_MODE", "The provided game mode is invalid.")]
+    InvalidGameMode,
+
+    /// `409 CONFLICT`
+    /// An error that occurs because the client tried to submit a beatmapset
+    /// that was already submitted for the same game mode.
+    #[error(StatusCode::CONFLICT, "ERR_SUBMISSION_EXISTS", "This beatmapset has already been submitted for this game mode.")]
+    SubmissionExists,
+
+    /// REVIEWS ///
+
+    /// `409 CONFLICT`
+    /// An error that occurs because the client tried to review a submission
+    /// they already reviewed for the same game mode.
+    #[error(StatusCode::CONFLICT, "ERR_REVIEW_EXISTS", "You have already reviewed this submission for this game mode.")]
+    ReviewExists,
+
+    /// RATINGS ///
+
+    /// `400 BAD REQUEST`
+    /// An error that occurs because the client sent a rating outside of the
+    /// allowed range.
+    #[error(StatusCode::BAD_REQUEST, "ERR_INVALID_RATING", "The provided rating must be between {min} and {max}.")]
+    InvalidRating {
+        min: i16,
+        max: i16
+    },
+
+    /// `403 FORBIDDEN`
+    /// An error that occurs because the client tried to rate their own
+    /// submission or review.
+    #[error(StatusCode::FORBIDDEN, "ERR_SELF_RATING", "You cannot rate your own submission or review.")]
+    SelfRating,
+
+    /// ROUNDS ///
+
+    /// `400 BAD REQUEST`
+    /// An error that occurs because the client sent round dates that are out
+    /// of order, such as voting ending before it starts.
+    #[error(StatusCode::BAD_REQUEST, "ERR_INVALID_ROUND_DATES", "Nominations must close before voting starts, and voting must start before it ends.")]
+    InvalidRoundDates,
+
+    /// `409 CONFLICT`
+    /// An error that occurs because the client tried to move a round back to
+    /// an earlier status, skip one, or move it before its dates allow it.
+    #[error(StatusCode::CONFLICT, "ERR_INVALID_ROUND_STATUS", "Rounds move from planning to voting to done, once their voting dates have been reached.")]
+    InvalidRoundStatus,
+
+    /// NOMINATIONS ///
+
+    /// `400 BAD REQUEST`
+    /// An error that occurs because the client tried to exclude difficulties
+    /// that aren't part of the nominated game mode, or all of them.
+    #[error(StatusCode::BAD_REQUEST, "ERR_INVALID_EXCLUDED_BEATMAPS", "Excluded difficulties must belong to the beatmapset in this game mode, and at least one must remain.")]
+    InvalidExcludedBeatmaps,
+
+    /// `400 BAD REQUEST`
+    /// An error that occurs because the client linked submissions that aren't
+    /// for the nominated beatmapset and game mode.
+    #[error(StatusCode::BAD_REQUEST, "ERR_INVALID_NOMINATION_SUBMISSIONS", "The provided submissions must be for this beatmapset and game mode.")]
+    InvalidNominationSubmissions,
+
+    /// `400 BAD REQUEST`
+    /// An error that occurs because the client sent an order that doesn't
+    /// list every nomination of the game mode exactly once.
+    #[error(StatusCode::BAD_REQUEST, "ERR_INVALID_NOMINATION_ORDER", "The order must list every nomination of this game mode exactly once.")]
+    InvalidNominationOrder,
+
+    /// `409 CONFLICT`
+    /// An error that occurs because the client tried to nominate a beatmapset
+    /// that is already nominated for the same round and game mode.
+    #[error(StatusCode::CONFLICT, "ERR_NOMINATION_EXISTS", "This beatmapset has already been nominated for this round and game mode.")]
+    NominationExists,
+
+    /// `409 CONFLICT`
+    /// An error that occurs because the client tried to change the
+    /// nominations of a round that is past planning, or whose nominations
+    /// have closed.
+    #[error(StatusCode::CONFLICT, "ERR_NOMINATIONS_CLOSED", "Nominations for this round have closed.")]
+    NominationsClosed,
+
     /// GENERIC ///
 
     /// `400 BAD REQUEST`
@@ -48,6 +156,12 @@ pub enum LovedError {
     #[error(StatusCode::UNAUTHORIZED, "ERR_UNAUTHORIZED", "The provided authorization key and/or session token is invalid.")]
     Unauthorized,
     
+    /// `403 FORBIDDEN`
+    /// An error that occurs because the client is authenticated, but isn't
+    /// allowed to perform this action.
+    #[error(StatusCode::FORBIDDEN, "ERR_FORBIDDEN", "You do not have permission to perform this action.")]
+    Forbidden,
+
     /// `404 NOT FOUND`
     /// The client tried to request an endpoint that doesn't exist.
     #[error(StatusCode::NOT_FOUND, "ERR_NOT_FOUND", "The requested endpoint was not found.")]
@@ -153,7 +267,9 @@ impl From<AthenaError> for LovedError {
     fn from(error: AthenaError) -> Self {
         match error {
             AthenaError::DbErr(_) => LovedError::DatabaseError,
-            AthenaError::ModelNotFound(model) => LovedError::ModelNotFound { model }
+            AthenaError::ModelNotFound(model) => LovedError::ModelNotFound { model },
+            AthenaError::OsuErr(error) => error.into(),
+            AthenaError::MissingTokens => LovedError::InvalidTokenState
         }
     }
 }
\ No newline at end of file
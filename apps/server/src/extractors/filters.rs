@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use athena::{entities::{beatmapsets, ratings, sea_orm_active_enums::RatingType, submissions}, prelude::submissions::DisplaySubmission};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use futures_util::future::{ready, Ready};
+use sea_orm::{
+    sea_query::{Alias, Expr, Func, LikeExpr, Query, SimpleExpr, SubQueryStatement},
+    ColumnTrait, Condition, IntoSimpleExpr, JoinType, Order, QueryFilter, QuerySelect, RelationTrait, Select
+};
+
+use super::pagination::{Cursor, CursorKey};
+use crate::errors::LovedError;
+
+pub enum SubmissionSort {
+    Rating,
+    PlayCount,
+    FavoriteCount,
+    Date
+}
+
+/// Filtering, sorting and search parameters accepted by the submission
+/// listing, meant to be used alongside [`Pagination`].
+///
+/// [`Pagination`]: super::pagination::Pagination
+pub struct SubmissionFilters {
+    pub game_mode: Option<i16>,
+    pub submitter_id: Option<i32>,
+    pub creator_id: Option<i32>,
+    pub ranked_status: Option<i16>,
+    pub submitted_after: Option<NaiveDateTime>,
+    pub submitted_before: Option<NaiveDateTime>,
+    pub min_rating: Option<f64>,
+    pub search: Option<String>,
+    pub sort: SubmissionSort,
+    pub order: Order
+}
+
+impl FromRequest for SubmissionFilters {
+    type Error = LovedError;
+    type Future = Ready<Result<SubmissionFilters, LovedError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(SubmissionFilters::parse(req.query_string()))
+    }
+}
+
+impl SubmissionFilters {
+    fn parse(query_string: &str) -> Result<Self, LovedError> {
+        let query = web::Query::<HashMap<String, String>>::from_query(query_string)
+            .map_err(|_| LovedError::BadRequest)?
+            .into_inner();
+
+        let mut filters = SubmissionFilters {
+            game_mode: parse_value(&query, "game_mode")?,
+            submitter_id: parse_value(&query, "submitter")?,
+            creator_id: parse_value(&query, "creator")?,
+            ranked_status: parse_value(&query, "ranked_status")?,
+            submitted_after: parse_date(&query, "submitted_after")?,
+            submitted_before: parse_date(&query, "submitted_before")?,
+            min_rating: parse_value(&query, "min_rating")?,
+            search: query.get("q")
+                .map(|search| search.trim().to_string())
+                .filter(|search| !search.is_empty()),
+            sort: SubmissionSort::Date,
+            order: Order::Desc
+        };
+
+        if filters.game_mode.is_some_and(|game_mode| !(0..=3).contains(&game_mode)) {
+            return Err(LovedError::InvalidQueryParameter { parameter: "game_mode" });
+        }
+
+        if let Some(sort) = query.get("sort") {
+            filters.sort = match sort.as_str() {
+                "rating" => SubmissionSort::Rating,
+                "play_count" => SubmissionSort::PlayCount,
+                "favorite_count" => SubmissionSort::FavoriteCount,
+                "date" => SubmissionSort::Date,
+                _ => return Err(LovedError::InvalidQueryParameter { parameter: "sort" })
+            };
+        }
+
+        if let Some(order) = query.get("order") {
+            filters.order = match order.as_str() {
+                "asc" => Order::Asc,
+                "desc" => Order::Desc,
+                _ => return Err(LovedError::InvalidQueryParameter { parameter: "order" })
+            };
+        }
+
+        Ok(filters)
+    }
+
+    /// Applies every filter and the search to a submission query.
+    pub fn apply(&self, query: Select<submissions::Entity>) -> Select<submissions::Entity> {
+        let mut condition = Condition::all();
+
+        if let Some(game_mode) = self.game_mode {
+            condition = condition.add(submissions::Column::GameMode.eq(game_mode));
+        }
+
+        if let Some(submitter_id) = self.submitter_id {
+            condition = condition.add(submissions::Column::SubmitterId.eq(submitter_id));
+        }
+
+        if let Some(creator_id) = self.creator_id {
+            condition = condition.add(beatmapsets::Column::CreatorId.eq(creator_id));
+        }
+
+        if let Some(ranked_status) = self.ranked_status {
+            condition = condition.add(beatmapsets::Column::RankedStatus.eq(ranked_status));
+        }
+
+        if let Some(submitted_after) = self.submitted_after {
+            condition = condition.add(submissions::Column::SubmittedAt.gte(submitted_after));
+        }
+
+        if let Some(submitted_before) = self.submitted_before {
+            condition = condition.add(submissions::Column::SubmittedAt.lte(submitted_before));
+        }
+
+        if let Some(min_rating) = self.min_rating {
+            condition = condition.add(
+                submissions::Column::Id.in_subquery(
+                    Query::select()
+                        .column(ratings::Column::ObjectId)
+                        .from(ratings::Entity)
+                        .and_where(ratings::Column::ReviewType.eq(RatingType::Submission))
+                        .group_by_col(ratings::Column::ObjectId)
+                        .and_having(Expr::expr(Func::avg(Expr::col(ratings::Column::Value))).gte(min_rating))
+                        .to_owned()
+                )
+            );
+        }
+
+        if let Some(search) = &self.search {
+            let pattern = LikeExpr::new(format!("%{}%", escape_like(&search.to_lowercase()))).escape('\\');
+
+            condition = condition.add(
+                Condition::any()
+                    .add(Expr::expr(Func::lower(Expr::col((beatmapsets::Entity, beatmapsets::Column::Artist)))).like(pattern.clone()))
+                    .add(Expr::expr(Func::lower(Expr::col((beatmapsets::Entity, beatmapsets::Column::Title)))).like(pattern.clone()))
+                    .add(Expr::expr(Func::lower(Expr::col((beatmapsets::Entity, beatmapsets::Column::CreatorName)))).like(pattern))
+            );
+        }
+
+        query
+            .join(JoinType::InnerJoin, submissions::Relation::Beatmapsets.def())
+            .filter(condition)
+    }
+
+    /// The expression submissions are sorted by, meant to be used as the
+    /// pagination key. Unrated submissions sort as if they had a rating of 0.
+    pub fn sort_key(&self) -> SimpleExpr {
+        match self.sort {
+            SubmissionSort::Rating => Func::coalesce([average_rating(), Expr::val(0f64).into()]).into(),
+            SubmissionSort::PlayCount => beatmapsets::Column::PlayCount.into_simple_expr(),
+            SubmissionSort::FavoriteCount => beatmapsets::Column::FavoriteCount.into_simple_expr(),
+            SubmissionSort::Date => submissions::Column::SubmittedAt.into_simple_expr()
+        }
+    }
+
+    /// Builds the cursor pointing at a submission, using the current sort key.
+    pub fn cursor_of(&self, submission: &DisplaySubmission) -> Cursor {
+        let key = match self.sort {
+            SubmissionSort::Rating => CursorKey::Ratio(submission.rating_sum, submission.rating_count as i64),
+            SubmissionSort::PlayCount => CursorKey::Int(submission.beatmapset.as_ref().map_or(0, |b| b.play_count).into()),
+            SubmissionSort::FavoriteCount => CursorKey::Int(submission.beatmapset.as_ref().map_or(0, |b| b.favorite_count).into()),
+            SubmissionSort::Date => CursorKey::Date(submission.submitted_at)
+        };
+
+        Cursor { key, id: submission.id }
+    }
+}
+
+/// The average rating of the submission in the current row. It's divided the
+/// same way as [`CursorKey::Ratio`] so that cursors compare equal to the row.
+fn average_rating() -> SimpleExpr {
+    let sum = Func::cast_as(Func::sum(Expr::col(ratings::Column::Value)), Alias::new("float8"));
+    let count = Func::cast_as(Func::count(Expr::col(ratings::Column::Value)), Alias::new("float8"));
+
+    SimpleExpr::SubQuery(
+        None,
+        Box::new(SubQueryStatement::SelectStatement(
+            Query::select()
+                .expr(Expr::expr(sum).div(count))
+                .from(ratings::Entity)
+                .and_where(ratings::Column::ReviewType.eq(RatingType::Submission))
+                .and_where(
+                    Expr::col((ratings::Entity, ratings::Column::ObjectId))
+                        .equals((submissions::Entity, submissions::Column::Id))
+                )
+                .to_owned()
+        ))
+    )
+}
+
+fn parse_value<T: std::str::FromStr>(query: &HashMap<String, String>, parameter: &'static str) -> Result<Option<T>, LovedError> {
+    query.get(parameter)
+        .map(|value| value.parse().map_err(|_| LovedError::InvalidQueryParameter { parameter }))
+        .transpose()
+}
+
+/// Accepts either an RFC 3339 timestamp or a plain `YYYY-MM-DD` date.
+fn parse_date(query: &HashMap<String, String>, parameter: &'static str) -> Result<Option<NaiveDateTime>, LovedError> {
+    query.get(parameter)
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|date| date.naive_utc())
+                .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|date| date.and_time(Default::default())))
+                .map_err(|_| LovedError::InvalidQueryParameter { parameter })
+        })
+        .transpose()
+}
+
+pub fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_like_escapes_wildcards() {
+        assert_eq!(escape_like("100%"), "100\\%");
+        assert_eq!(escape_like("my_map"), "my\\_map");
+    }
+
+    #[test]
+    fn escape_like_escapes_the_escape_character_first() {
+        // Escaping the backslash last would double the ones added for `%`.
+        assert_eq!(escape_like("a\\%b"), "a\\\\\\%b");
+    }
+
+    #[test]
+    fn escape_like_leaves_plain_text_alone() {
+        assert_eq!(escape_like("Camellia"), "Camellia");
+    }
+
+    #[test]
+    fn parse_date_accepts_timestamps_and_dates() {
+        let query = HashMap::from([
+            ("from".to_string(), "2025-04-18T12:30:00+02:00".to_string()),
+            ("to".to_string(), "2025-04-18".to_string())
+        ]);
+
+        assert_eq!(
+            parse_date(&query, "from").ok().flatten(),
+            NaiveDate::from_ymd_opt(2025, 4, 18).and_then(|date| date.and_hms_opt(10, 30, 0))
+        );
+        assert_eq!(
+            parse_date(&query, "to").ok().flatten(),
+            NaiveDate::from_ymd_opt(2025, 4, 18).and_then(|date| date.and_hms_opt(0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn parse_date_rejects_anything_else() {
+        let query = HashMap::from([("from".to_string(), "yesterday".to_string())]);
+
+        assert!(parse_date(&query, "from").is_err());
+        assert!(parse_date(&query, "to").is_ok_and(|date| date.is_none()));
+    }
+}
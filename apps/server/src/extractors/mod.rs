@@ -1,2 +1,3 @@
+pub mod filters;
 pub mod pagination;
 pub mod session;
\ No newline at end of file
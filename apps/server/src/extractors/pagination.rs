@@ -1,26 +1,94 @@
 use std::future::Future;
 
-use actix_web::{FromRequest, HttpRequest, Responder};
+use actix_web::{http::header, CustomizeResponder, FromRequest, HttpRequest, Responder};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::NaiveDateTime;
 use futures_util::future::LocalBoxFuture;
-use serde::Serialize;
+use sea_orm::{
+    sea_query::{Alias, Expr, IntoCondition, SimpleExpr},
+    EntityTrait, IntoSimpleExpr, Order, QueryFilter, QueryOrder, QuerySelect, Select
+};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::{errors::LovedError, service::Response};
+use crate::{errors::LovedError, service::ResponseWithMetadata};
 
-pub struct Pagination<const HARD_LIMIT: usize, Output = Value> 
+/// Page-based pagination over a resource. Resources that also support keyset
+/// pagination set `KEYSET`; everywhere else the `cursor` parameter is rejected.
+pub struct Pagination<const HARD_LIMIT: usize, Output = Value, const KEYSET: bool = false>
     where Output: Serialize
 {
     pub page: u32,
     pub limit: usize,
     pub total: usize,
     pub data: Vec<Output>,
+    /// Set when the client asked for keyset pagination. An empty `cursor`
+    /// parameter starts from the first item, so it's `Some(None)` there.
+    pub cursor: Option<Option<Cursor>>,
+    pub next_cursor: Option<Cursor>,
+    path: String,
+    query: Vec<String>,
 }
 
-impl<const HARD_LIMIT: usize, Output> FromRequest for Pagination<HARD_LIMIT, Output>
+/// The value of the sort key of a row, used to seek past it.
+#[derive(Serialize, Deserialize, Clone)]
+pub enum CursorKey {
+    Int(i64),
+    /// A sum divided by a count, such as an average. Both are kept as integers
+    /// and divided by the database, so the key compares equal to the row's.
+    Ratio(i64, i64),
+    Date(NaiveDateTime)
+}
+
+/// Points at the last row of a page, by its sort key and identifier.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Cursor {
+    pub key: CursorKey,
+    pub id: i32
+}
+
+#[derive(Serialize)]
+pub struct PaginationMeta {
+    pub limit: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pages: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+impl From<CursorKey> for SimpleExpr {
+    fn from(key: CursorKey) -> Self {
+        match key {
+            CursorKey::Int(value) => value.into(),
+            CursorKey::Ratio(_, 0) => 0f64.into(),
+            CursorKey::Ratio(sum, count) => Expr::val(sum).cast_as(Alias::new("float8"))
+                .div(Expr::val(count).cast_as(Alias::new("float8"))),
+            CursorKey::Date(value) => value.into()
+        }
+    }
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap())
+    }
+
+    pub fn decode(value: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(value).ok()?;
+
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+impl<const HARD_LIMIT: usize, Output, const KEYSET: bool> FromRequest for Pagination<HARD_LIMIT, Output, KEYSET>
     where Output: Serialize
 {
     type Error = LovedError;
-    type Future = LocalBoxFuture<'static, Result<Pagination<HARD_LIMIT, Output>, LovedError>>;
+    type Future = LocalBoxFuture<'static, Result<Pagination<HARD_LIMIT, Output, KEYSET>, LovedError>>;
 
     fn from_request(
         req: &HttpRequest,
@@ -34,53 +102,310 @@ impl<const HARD_LIMIT: usize, Output> FromRequest for Pagination<HARD_LIMIT, Out
             let query_string = querystring::querify(req.query_string());
             let mut page = 1;
             let mut limit = HARD_LIMIT;
-    
+            let mut cursor = None;
+            let mut query = Vec::new();
+
             for (key, value) in query_string {
                 if key == "page" {
-                    let parsed_page = value.parse().unwrap();
-    
+                    let parsed_page = value.parse().map_err(|_| LovedError::InvalidPage)?;
+
                     if parsed_page > 0 {
                         page = parsed_page;
                     } else {
                         return Err(LovedError::InvalidPage);
                     }
                 } else if key == "limit" {
-                    let parsed_limit: usize = value.parse().unwrap();
-    
+                    let parsed_limit: usize = value.parse()
+                        .map_err(|_| LovedError::PaginationLimitInvalid { min: 1, max: HARD_LIMIT })?;
+
                     if parsed_limit > HARD_LIMIT || parsed_limit < 1 {
                         return Err(LovedError::PaginationLimitInvalid { min: 1, max: HARD_LIMIT });
                     }
 
                     limit = parsed_limit;
+                } else if key == "cursor" {
+                    if !KEYSET {
+                        return Err(LovedError::InvalidCursor);
+                    }
+
+                    if value.is_empty() {
+                        cursor = Some(None);
+                    } else {
+                        let parsed_cursor = Cursor::decode(value).ok_or(LovedError::InvalidCursor)?;
+
+                        cursor = Some(Some(parsed_cursor));
+                    }
+                } else {
+                    // Kept as-is (still percent-encoded) for the `Link` header.
+                    query.push(format!("{}={}", key, value));
                 }
             }
-    
-            Ok(Pagination { page, limit, total: 0, data: Vec::new() })
+
+            Ok(Pagination {
+                page,
+                limit,
+                total: 0,
+                data: Vec::new(),
+                cursor,
+                next_cursor: None,
+                path: req.path().to_string(),
+                query
+            })
         })
     }
 }
 
-impl<const HARD_LIMIT: usize, Output> Pagination<HARD_LIMIT, Output> 
+impl<const HARD_LIMIT: usize, Output, const KEYSET: bool> Pagination<HARD_LIMIT, Output, KEYSET>
     where Output: Serialize
 {
     pub fn get_page_offset(&self) -> u32 {
         (self.page - 1) * self.limit as u32
     }
 
-    pub async fn provide<Fut>(mut self, fun: impl FnOnce(&Pagination<HARD_LIMIT, Output>) -> Fut) -> Result<Self, LovedError>
-        where Fut: Future<Output = Result<Vec<Output>, LovedError>>
-    {
-        self.data = fun(&self).await?;
-        Ok(self)
+    pub fn get_page_count(&self) -> usize {
+        self.total.div_ceil(self.limit)
+    }
+
+    pub fn is_cursor(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    /// Orders the query by `key` and then `id`, and narrows it down to the
+    /// current page, either by offset or by seeking past the cursor.
+    pub fn paginate<E: EntityTrait>(&self, query: Select<E>, key: SimpleExpr, id: E::Column, order: Order) -> Select<E> {
+        let id = id.into_simple_expr();
+        let mut query = query
+            .order_by(key.clone(), order.clone())
+            .order_by(id.clone(), order.clone())
+            .limit(self.limit as u64);
+
+        match &self.cursor {
+            Some(Some(cursor)) => {
+                let row = Expr::tuple([key, id]);
+                let last = Expr::tuple([cursor.key.clone().into(), cursor.id.into()]);
+
+                query = query.filter(match order {
+                    Order::Asc => row.gt(last),
+                    _ => row.lt(last)
+                }.into_condition());
+            }
+            Some(None) => {}
+            None => {
+                query = query.offset(self.get_page_offset() as u64);
+            }
+        }
+
+        query
     }
 
     pub fn respond(
         self,
-    ) -> Result<Response<Vec<Output>>, LovedError> {
-        Ok(Response {
+    ) -> Result<CustomizeResponder<ResponseWithMetadata<Vec<Output>, PaginationMeta>>, LovedError> {
+        let links = self.get_links();
+        let meta = if self.is_cursor() {
+            PaginationMeta {
+                limit: self.limit,
+                page: None,
+                total: None,
+                pages: None,
+                next_cursor: self.next_cursor.as_ref().map(Cursor::encode)
+            }
+        } else {
+            PaginationMeta {
+                limit: self.limit,
+                page: Some(self.page),
+                total: Some(self.total),
+                pages: Some(self.get_page_count()),
+                next_cursor: None
+            }
+        };
+
+        let response = ResponseWithMetadata {
             status: 200,
             message: None,
-            data: Some(self.data)
-        })
+            data: Some(self.data),
+            meta
+        }
+        .customize();
+
+        if links.is_empty() {
+            Ok(response)
+        } else {
+            Ok(response.insert_header((header::LINK, links.join(", "))))
+        }
     }
-}
\ No newline at end of file
+
+    /// Builds the RFC 8288 links pointing to the surrounding pages.
+    fn get_links(&self) -> Vec<String> {
+        let mut links = Vec::new();
+
+        if self.is_cursor() {
+            links.push(self.get_link("cursor=".to_string(), "first"));
+
+            if let Some(next_cursor) = &self.next_cursor {
+                links.push(self.get_link(format!("cursor={}", next_cursor.encode()), "next"));
+            }
+
+            return links;
+        }
+
+        let pages = self.get_page_count();
+        let page = self.page as usize;
+
+        if pages == 0 {
+            return links;
+        }
+
+        links.push(self.get_link("page=1".to_string(), "first"));
+
+        if page > 1 {
+            links.push(self.get_link(format!("page={}", page.min(pages + 1) - 1), "prev"));
+        }
+
+        if page < pages {
+            links.push(self.get_link(format!("page={}", page + 1), "next"));
+        }
+
+        links.push(self.get_link(format!("page={}", pages), "last"));
+        links
+    }
+
+    fn get_link(&self, position: String, rel: &str) -> String {
+        let mut query = self.query.clone();
+
+        query.push(position);
+        query.push(format!("limit={}", self.limit));
+
+        format!("<{}?{}>; rel=\"{}\"", self.path, query.join("&"), rel)
+    }
+}
+
+impl<const HARD_LIMIT: usize, Output> Pagination<HARD_LIMIT, Output>
+    where Output: Serialize
+{
+    /// Fills in the page using the provided function, which has to resolve to
+    /// the items of the current page along with the total amount of items
+    /// across every page.
+    pub async fn provide<Fut>(mut self, fun: impl FnOnce(&Pagination<HARD_LIMIT, Output>) -> Fut) -> Result<Self, LovedError>
+        where Fut: Future<Output = Result<(Vec<Output>, usize), LovedError>>
+    {
+        let (data, total) = fun(&self).await?;
+
+        self.data = data;
+        self.total = total;
+        Ok(self)
+    }
+}
+
+impl<const HARD_LIMIT: usize, Output> Pagination<HARD_LIMIT, Output, true>
+    where Output: Serialize
+{
+    /// Same as [`Pagination::provide`], for resources that opted into keyset
+    /// pagination. `cursor_of` builds the cursor pointing at an item, and the
+    /// total may be left out (as `0`) when a cursor is in use.
+    pub async fn provide_with_cursor<Fut>(
+        mut self,
+        fun: impl FnOnce(&Pagination<HARD_LIMIT, Output, true>) -> Fut,
+        cursor_of: impl Fn(&Output) -> Cursor
+    ) -> Result<Self, LovedError>
+        where Fut: Future<Output = Result<(Vec<Output>, usize), LovedError>>
+    {
+        let (data, total) = fun(&self).await?;
+
+        if self.is_cursor() && data.len() == self.limit {
+            self.next_cursor = data.last().map(cursor_of);
+        }
+
+        self.data = data;
+        self.total = total;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pagination(page: u32, limit: usize, total: usize) -> Pagination<50> {
+        Pagination {
+            page,
+            limit,
+            total,
+            data: Vec::new(),
+            cursor: None,
+            next_cursor: None,
+            path: "/submissions".to_string(),
+            query: vec!["sort=date".to_string()]
+        }
+    }
+
+    #[test]
+    fn links_surround_the_current_page() {
+        assert_eq!(pagination(2, 10, 35).get_links(), vec![
+            "</submissions?sort=date&page=1&limit=10>; rel=\"first\"",
+            "</submissions?sort=date&page=1&limit=10>; rel=\"prev\"",
+            "</submissions?sort=date&page=3&limit=10>; rel=\"next\"",
+            "</submissions?sort=date&page=4&limit=10>; rel=\"last\""
+        ]);
+    }
+
+    #[test]
+    fn links_skip_prev_on_the_first_page_and_next_on_the_last() {
+        let first = pagination(1, 10, 20).get_links();
+        let last = pagination(2, 10, 20).get_links();
+
+        assert!(!first.iter().any(|link| link.ends_with("rel=\"prev\"")));
+        assert!(first.iter().any(|link| link.ends_with("rel=\"next\"")));
+        assert!(last.iter().any(|link| link.ends_with("rel=\"prev\"")));
+        assert!(!last.iter().any(|link| link.ends_with("rel=\"next\"")));
+    }
+
+    #[test]
+    fn links_past_the_last_page_point_back_to_it() {
+        assert!(pagination(9, 10, 20).get_links().contains(&"</submissions?sort=date&page=2&limit=10>; rel=\"prev\"".to_string()));
+    }
+
+    #[test]
+    fn links_are_empty_without_items() {
+        assert!(pagination(1, 10, 0).get_links().is_empty());
+    }
+
+    #[test]
+    fn cursor_links_point_to_the_first_and_next_page() {
+        let mut pagination = pagination(1, 10, 0);
+        let next = Cursor { key: CursorKey::Int(7), id: 3 };
+
+        pagination.cursor = Some(None);
+        pagination.next_cursor = Some(next.clone());
+
+        assert_eq!(pagination.get_links(), vec![
+            "</submissions?sort=date&cursor=&limit=10>; rel=\"first\"".to_string(),
+            format!("</submissions?sort=date&cursor={}&limit=10>; rel=\"next\"", next.encode())
+        ]);
+    }
+
+    #[test]
+    fn cursors_survive_a_round_trip() {
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 4, 18).and_then(|date| date.and_hms_opt(12, 30, 0)).unwrap();
+
+        for key in [CursorKey::Int(-5), CursorKey::Ratio(14, 3), CursorKey::Date(date)] {
+            let cursor = Cursor { key, id: 42 };
+            let decoded = Cursor::decode(&cursor.encode()).expect("encoded cursors decode");
+
+            assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&cursor).unwrap());
+        }
+    }
+
+    #[test]
+    fn cursors_are_url_safe() {
+        let encoded = Cursor { key: CursorKey::Ratio(i64::MAX, i64::MAX), id: i32::MAX }.encode();
+
+        assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+
+    #[test]
+    fn malformed_cursors_are_rejected() {
+        assert!(Cursor::decode("not a cursor").is_none());
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode(b"{\"id\":1}")).is_none());
+    }
+}
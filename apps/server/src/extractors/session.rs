@@ -1,13 +1,21 @@
-use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
-use athena::prelude::users::FullUser;
+use actix_web::{
+    body::MessageBody, cookie::{time, Cookie, SameSite}, dev::{Payload, ServiceRequest, ServiceResponse},
+    http::header::{self, HeaderValue}, middleware::Next, web, Error, FromRequest, HttpMessage, HttpRequest
+};
+use athena::{errors::AthenaError, prelude::{sessions::SESSION_LIFETIME, users::FullUser}};
 use futures_util::future::LocalBoxFuture;
 
 use crate::{errors::LovedError, state::LovedState};
 
 pub struct Session {
-    pub user: FullUser
+    pub user: FullUser,
+    pub token: String
 }
 
+/// Left in the request's extensions when a session read from the cookie got
+/// renewed, for [`refresh_cookie`] to pick up.
+struct RenewedSession(String);
+
 impl FromRequest for Session {
     type Error = LovedError;
     type Future = LocalBoxFuture<'static, Result<Session, LovedError>>;
@@ -18,13 +26,75 @@ impl FromRequest for Session {
         Box::pin(async move {
             let state = req.app_data::<web::Data<LovedState>>().unwrap();
 
-            if let Some(session) = req.cookie("session") {
-                return Ok(Session {
-                    user: FullUser::from_session(session.value(), &state.db_pool).await?
-                })
+            // Browsers go through the cookie, while scripts can pass the
+            // token along as a bearer token.
+            let cookie = req.cookie("session").map(|cookie| cookie.value().to_string());
+            let from_cookie = cookie.is_some();
+            let token = cookie.or_else(|| bearer_token(&req));
+
+            if let Some(token) = token {
+                let (user, renewed) = FullUser::from_session(&token, &state.db_pool)
+                    .await
+                    .map_err(|error| match error {
+                        // Unknown and expired sessions both end up here.
+                        AthenaError::ModelNotFound(_) => LovedError::Unauthorized,
+                        error => error.into()
+                    })?;
+
+                if renewed && from_cookie {
+                    req.extensions_mut().insert(RenewedSession(token.clone()));
+                }
+
+                state.refresh_stale_users([&user.base]).await;
+
+                return Ok(Session { user, token })
             }
 
             Err(LovedError::Unauthorized)
         })
     }
-}
\ No newline at end of file
+}
+
+/// Sends the session cookie again when the request renewed its session, so
+/// that browsers keep it for as long as the session lasts. Responses already
+/// setting the cookie, such as logging out, are left alone.
+pub async fn refresh_cookie(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let mut res = next.call(req).await?;
+    let renewed = res.request().extensions_mut().remove::<RenewedSession>();
+
+    if let Some(RenewedSession(token)) = renewed {
+        if !res.headers().contains_key(header::SET_COOKIE) {
+            let cookie = session_cookie(token, time::Duration::seconds(SESSION_LIFETIME.num_seconds()));
+
+            if let Ok(value) = HeaderValue::from_str(&cookie.to_string()) {
+                res.headers_mut().append(header::SET_COOKIE, value);
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+pub fn session_cookie(token: String, max_age: time::Duration) -> Cookie<'static> {
+    Cookie::build("session", token)
+        .path("/")
+        .secure(true)
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .max_age(max_age)
+        .finish()
+}
+
+fn bearer_token(req: &HttpRequest) -> Option<String> {
+    let value = req.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+
+    if scheme.eq_ignore_ascii_case("bearer") && !token.trim().is_empty() {
+        Some(token.trim().to_string())
+    } else {
+        None
+    }
+}
@@ -1,5 +1,5 @@
 use std::env;
-use actix_web::{middleware::Logger, web, App, HttpServer};
+use actix_web::{middleware::{from_fn, Logger}, web, App, HttpServer};
 use dotenvy::dotenv;
 use errors::LovedError;
 use state::LovedState;
@@ -27,6 +27,7 @@ async fn main() -> std::io::Result<()> {
 
     let server = HttpServer::new(move || {
         App::new()
+            .wrap(from_fn(extractors::session::refresh_cookie))
             .wrap(Logger::new("%a %{User-Agent}i"))
             .app_data(web::JsonConfig::default().error_handler(|err, _| LovedError::from(err).into()))
             .app_data(web::Data::new(state.clone()))
@@ -34,7 +35,48 @@ async fn main() -> std::io::Result<()> {
             .service(
                 web::scope("/oauth")
                     .service(routes::oauth::start_token)
-                    .service(routes::oauth::login_token_callback),
+                    .service(routes::oauth::login_token_callback)
+                    .service(routes::oauth::logout)
+                    .service(routes::oauth::logout_all),
+            )
+            // /submissions
+            .service(
+                web::scope("/submissions")
+                    .service(routes::submissions::index)
+                    .service(routes::submissions::create)
+                    .service(routes::reviews::index)
+                    .service(routes::reviews::create)
+                    .service(routes::reviews::update)
+                    .service(routes::reviews::remove)
+                    .service(routes::ratings::rate_submission)
+                    .service(routes::ratings::unrate_submission)
+                    .service(routes::ratings::rate_review)
+                    .service(routes::ratings::unrate_review),
+            )
+            // /users
+            .service(
+                web::scope("/users")
+                    .service(routes::users::index)
+                    .service(routes::users::show),
+            )
+            // /rounds
+            .service(
+                web::scope("/rounds")
+                    .service(routes::rounds::index)
+                    .service(routes::rounds::create)
+                    .service(routes::rounds::show)
+                    .service(routes::rounds::update)
+                    .service(routes::nominations::index)
+                    .service(routes::nominations::create)
+                    .service(routes::nominations::reorder)
+                    .service(routes::nominations::remove),
+            )
+            // /queues
+            .service(
+                web::scope("/queues")
+                    .service(routes::queues::index)
+                    .service(routes::queues::requeue_dead)
+                    .service(routes::queues::purge_dead),
             )
             .default_service(web::route().to(routes::handle_default))
     })
@@ -1,8 +1,14 @@
 use actix_web::HttpRequest;
 use crate::{errors::LovedError, service::Response};
 
+pub mod nominations;
 pub mod oauth;
+pub mod queues;
+pub mod ratings;
+pub mod reviews;
+pub mod rounds;
 pub mod submissions;
+pub mod users;
 
 pub async fn handle_default(request: HttpRequest) -> Result<Response, LovedError> {
     if request.resource_map().has_resource(request.path()) {
@@ -0,0 +1,254 @@
+use actix_web::{delete, get, post, put, web, Responder};
+use athena::{
+    entities::{nominations, sea_orm_active_enums::RoundStatus, submissions}, errors::AthenaError,
+    prelude::{beatmaps::FullBeatmapset, nominations::FullNomination, roles::Permissions, rounds::FullRound}
+};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, SqlErr};
+use serde::Deserialize;
+use crate::{errors::LovedError, extractors::session::Session, service::Response, state::LovedState};
+
+#[derive(Deserialize)]
+struct NominationsRequest {
+    game_mode: Option<i16>
+}
+
+#[get("/{round_id}/nominations")]
+pub async fn index(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<i32>,
+    query: web::Query<NominationsRequest>,
+) -> impl Responder {
+    if !session.user.has_permission(
+        Permissions::VIEW_ROUNDS | Permissions::MANAGE_ROUNDS | Permissions::MANAGE_PICKS | Permissions::MANAGE_GAMEMODE_PICKS
+    ) {
+        return Err(LovedError::Forbidden);
+    }
+
+    let round = FullRound::fetch(path.into_inner(), &state.db_pool).await?;
+    let game_mode = query.game_mode;
+    let nominations = FullNomination::find(&state.db_pool, |base| {
+        let base = base.filter(nominations::Column::RoundId.eq(round.base.id));
+        let base = match game_mode {
+            Some(game_mode) => base.filter(nominations::Column::GameMode.eq(game_mode)),
+            None => base
+        };
+
+        base.order_by_asc(nominations::Column::GameMode)
+            .order_by_asc(nominations::Column::Position)
+            .order_by_asc(nominations::Column::Id)
+    }).await?;
+
+    Ok(Response {
+        status: 200,
+        message: None,
+        data: Some(nominations.into_iter().map(|n| n.into_display()).collect::<Vec<_>>())
+    })
+}
+
+#[derive(Deserialize)]
+struct CreateNominationRequest {
+    game_mode: i16,
+    beatmapset_id: i32,
+    /// Defaults to every submission of the beatmapset for the game mode.
+    submission_ids: Option<Vec<i32>>,
+    #[serde(default)]
+    excluded_beatmap_ids: Vec<i32>,
+    #[serde(default)]
+    description: String
+}
+
+#[post("/{round_id}/nominations")]
+pub async fn create(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<i32>,
+    payload: web::Json<CreateNominationRequest>,
+) -> impl Responder {
+    let payload = payload.into_inner();
+
+    if !(0..=3).contains(&payload.game_mode) {
+        return Err(LovedError::InvalidGameMode);
+    }
+
+    let round = FullRound::fetch(path.into_inner(), &state.db_pool).await?;
+
+    ensure_can_pick(&session, &round, payload.game_mode)?;
+    ensure_nominations_open(&round)?;
+
+    let existing = nominations::Entity::find()
+        .filter(nominations::Column::RoundId.eq(round.base.id))
+        .filter(nominations::Column::GameMode.eq(payload.game_mode))
+        .filter(nominations::Column::BeatmapsetId.eq(payload.beatmapset_id))
+        .one(&state.db_pool)
+        .await?;
+
+    if existing.is_some() {
+        return Err(LovedError::NominationExists);
+    }
+
+    let beatmapset = FullBeatmapset::fetch(payload.beatmapset_id, &state.db_pool).await?;
+    let beatmap_ids: Vec<i32> = beatmapset.beatmaps
+        .iter()
+        .filter(|beatmap| beatmap.base.game_mode == payload.game_mode)
+        .map(|beatmap| beatmap.base.id)
+        .collect();
+
+    if beatmap_ids.is_empty() {
+        return Err(LovedError::InvalidGameMode);
+    }
+
+    let mut excluded_beatmap_ids = payload.excluded_beatmap_ids;
+    excluded_beatmap_ids.sort_unstable();
+    excluded_beatmap_ids.dedup();
+
+    // At least one difficulty has to be left to play.
+    if excluded_beatmap_ids.iter().any(|beatmap_id| !beatmap_ids.contains(beatmap_id))
+        || excluded_beatmap_ids.len() == beatmap_ids.len()
+    {
+        return Err(LovedError::InvalidExcludedBeatmaps);
+    }
+
+    let candidates: Vec<i32> = submissions::Entity::find()
+        .filter(submissions::Column::BeatmapsetId.eq(beatmapset.base.id))
+        .filter(submissions::Column::GameMode.eq(payload.game_mode))
+        .all(&state.db_pool)
+        .await?
+        .into_iter()
+        .map(|submission| submission.id)
+        .collect();
+
+    let submission_ids = match payload.submission_ids {
+        Some(mut submission_ids) => {
+            submission_ids.sort_unstable();
+            submission_ids.dedup();
+
+            if submission_ids.iter().any(|submission_id| !candidates.contains(submission_id)) {
+                return Err(LovedError::InvalidNominationSubmissions);
+            }
+
+            submission_ids
+        },
+        None => candidates
+    };
+
+    let nomination = FullNomination::create(nominations::ActiveModel {
+        id: sea_orm::ActiveValue::NotSet,
+        round_id: sea_orm::ActiveValue::Set(round.base.id),
+        game_mode: sea_orm::ActiveValue::Set(payload.game_mode),
+        beatmapset_id: sea_orm::ActiveValue::Set(beatmapset.base.id),
+        nominator_id: sea_orm::ActiveValue::Set(session.user.base.id),
+        position: sea_orm::ActiveValue::NotSet,
+        description: sea_orm::ActiveValue::Set(payload.description.trim().to_string())
+    }, submission_ids, excluded_beatmap_ids, &state.db_pool)
+        .await
+        .map_err(|error| match error {
+            // Covers a nomination created since the check above.
+            AthenaError::DbErr(ref err) if matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => LovedError::NominationExists,
+            error => error.into()
+        })?;
+
+    Ok(Response {
+        status: 200,
+        message: None,
+        data: Some(nomination.into_display())
+    })
+}
+
+#[derive(Deserialize)]
+struct ReorderNominationsRequest {
+    game_mode: i16,
+    nomination_ids: Vec<i32>
+}
+
+/// Reorders the nominations of a game mode, which all have to be listed.
+#[put("/{round_id}/nominations/order")]
+pub async fn reorder(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<i32>,
+    payload: web::Json<ReorderNominationsRequest>,
+) -> impl Responder {
+    if !(0..=3).contains(&payload.game_mode) {
+        return Err(LovedError::InvalidGameMode);
+    }
+
+    let round = FullRound::fetch(path.into_inner(), &state.db_pool).await?;
+
+    ensure_can_pick(&session, &round, payload.game_mode)?;
+    ensure_nominations_open(&round)?;
+
+    let mut current = FullNomination::ids_in_order(round.base.id, payload.game_mode, &state.db_pool).await?;
+    let mut requested = payload.nomination_ids.clone();
+
+    current.sort_unstable();
+    requested.sort_unstable();
+
+    if current != requested {
+        return Err(LovedError::InvalidNominationOrder);
+    }
+
+    FullNomination::reorder(&payload.nomination_ids, &state.db_pool).await?;
+
+    Ok(Response {
+        status: 200,
+        message: None,
+        data: None::<()>
+    })
+}
+
+#[delete("/{round_id}/nominations/{nomination_id}")]
+pub async fn remove(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<(i32, i32)>,
+) -> impl Responder {
+    let (round_id, nomination_id) = path.into_inner();
+    let nomination = FullNomination::fetch(nomination_id, &state.db_pool).await?;
+
+    if nomination.base.round_id != round_id {
+        return Err(LovedError::ModelNotFound { model: "nomination" });
+    }
+
+    let round = FullRound::fetch(round_id, &state.db_pool).await?;
+
+    ensure_can_pick(&session, &round, nomination.base.game_mode)?;
+    ensure_nominations_open(&round)?;
+
+    nomination.delete(&state.db_pool).await?;
+
+    Ok(Response {
+        status: 200,
+        message: None,
+        data: None::<()>
+    })
+}
+
+/// Captains can only pick nominations for the game modes they hold the
+/// round's captain slot of, while `MANAGE_PICKS` covers every game mode.
+fn ensure_can_pick(session: &Session, round: &FullRound, game_mode: i16) -> Result<(), LovedError> {
+    let user_id = session.user.base.id;
+    let is_captain = round.game_modes
+        .iter()
+        .any(|slot| slot.base.game_mode == game_mode && slot.base.captain_id == Some(user_id));
+
+    if session.user.has_permission(Permissions::MANAGE_PICKS)
+        || (is_captain && session.user.has_game_mode_permission(Permissions::MANAGE_GAMEMODE_PICKS, game_mode))
+    {
+        Ok(())
+    } else {
+        Err(LovedError::Forbidden)
+    }
+}
+
+/// Nominations can only change while the round is being planned, and until
+/// they close.
+fn ensure_nominations_open(round: &FullRound) -> Result<(), LovedError> {
+    let closed = round.base.nominations_close_at.is_some_and(|close_at| close_at <= chrono::Utc::now().naive_utc());
+
+    if round.base.status == RoundStatus::Planning && !closed {
+        Ok(())
+    } else {
+        Err(LovedError::NominationsClosed)
+    }
+}
@@ -1,12 +1,10 @@
-use actix_web::{get, web, Responder};
-use athena::{entities::{sessions, users}, prelude::users::FullUser};
+use actix_web::{cookie::time, get, http::header, post, web, CustomizeResponder, Responder};
+use athena::prelude::{sessions::{FullSession, SESSION_LIFETIME}, tokens::OsuTokens, users::FullUser};
 use redis::Commands;
-use sea_orm::{ActiveModelTrait, IntoActiveModel};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::{errors::LovedError, service::{self, Response}, state::LovedState};
-
+use crate::{errors::LovedError, extractors::session::{session_cookie, Session}, service::{self, Response}, state::LovedState};
 
 #[derive(Deserialize)]
 struct StartTokenRequest {
@@ -111,58 +109,58 @@ pub async fn login_token_callback(
             Err(LovedError::InvalidTokenState)
         } else {
             let user_client = state.get_osu_client(query.code.clone(), "/login").await?;
-            let user = user_client.own_data().await?;
-            let mut display_user = None;
-
-            // TODO: Move this to a seperate function that does this
-            if let Ok(existing) = FullUser::fetch(user.user_id.try_into().unwrap_or(0), &state.db_pool).await {
-                let mut existing = existing.base.into_active_model();
-                
-                if existing.username.as_ref() != &user.username.to_string() {
-                    // TODO: Store previous usernames
-                    existing.set(users::Column::Username, user.username.to_string().into());
-                }
-            
-                if existing.is_changed() {
-                    display_user = Some(FullUser::update(existing, &state.db_pool).await?.into_display());
-                }
-            } else {
-                display_user = Some(FullUser::create(users::ActiveModel {
-                    id: sea_orm::ActiveValue::Set(user.user_id.try_into().unwrap()),
-                    username: sea_orm::ActiveValue::Set(user.username.to_string()),
-                    country: sea_orm::ActiveValue::Set(Some(user.country_code.to_string())),
-                    restricted: sea_orm::ActiveValue::Set(user.is_restricted.unwrap_or(false)),
-                    api_fetched_at: sea_orm::ActiveValue::Set(chrono::Utc::now().naive_utc()),
-                    tokens: sea_orm::ActiveValue::Set(serde_json::json!({})) // TODO: Securely store tokens
-                }, &state.db_pool).await?.into_display());
-            }
+            let mut user = FullUser::upsert_from_osu(&user_client.own_data().await?, &state.db_pool).await?;
+
+            user.store_tokens(&OsuTokens::from_token(&user_client.token()), &state.db_pool).await?;
+
+            let token = service::generate_token();
+            FullSession::create(user.base.id, &token, &state.db_pool).await?;
 
-            if let Some(display_user) = display_user {
-                let token = service::generate_token();
-                let session = sessions::ActiveModel {
-                    id: sea_orm::ActiveValue::NotSet,
-                    user_id: sea_orm::ActiveValue::Set(user.user_id.try_into().unwrap()),
-                    session_token: sea_orm::ActiveValue::Set(token.clone()),
-                    expires_at: sea_orm::ActiveValue::Set(chrono::Utc::now().naive_utc() + chrono::Duration::days(30)),
-                };
-
-                session.insert(&state.db_pool)
-                    .await?;
-
-                Ok(Response {
-                    status: 200,
-                    message: None,
-                    data: Some(json!({
-                        "token": token,
-                        "user": display_user
-                    }))
-                })
-            } else {
-                // This shouldn't be possible.
-                Err(LovedError::InternalError)
+            let cookie = session_cookie(token.clone(), time::Duration::seconds(SESSION_LIFETIME.num_seconds()));
+
+            Ok(Response {
+                status: 200,
+                message: None,
+                data: Some(json!({
+                    "token": token,
+                    "user": user.into_display()
+                }))
             }
+            .customize()
+            .append_header((header::SET_COOKIE, cookie.to_string())))
         }
     } else {
         Err(LovedError::InvalidTokenAuthType)
     }
-}
\ No newline at end of file
+}
+
+#[post("/logout")]
+pub async fn logout(
+    state: web::Data<LovedState>,
+    session: Session
+) -> impl Responder {
+    FullSession::delete(&session.token, &state.db_pool).await?;
+
+    Ok::<_, LovedError>(logged_out())
+}
+
+/// Ends every session of the user, including the current one.
+#[post("/logout-all")]
+pub async fn logout_all(
+    state: web::Data<LovedState>,
+    session: Session
+) -> impl Responder {
+    FullSession::delete_all(session.user.base.id, &state.db_pool).await?;
+
+    Ok::<_, LovedError>(logged_out())
+}
+
+fn logged_out() -> CustomizeResponder<Response> {
+    Response {
+        status: 200,
+        message: None,
+        data: None
+    }
+    .customize()
+    .append_header((header::SET_COOKIE, session_cookie(String::new(), time::Duration::ZERO).to_string()))
+}
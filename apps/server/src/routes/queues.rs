@@ -0,0 +1,82 @@
+use actix_web::{delete, get, post, web, Responder};
+use athena::{jobs::{self, queues}, prelude::roles::Permissions};
+use serde_json::json;
+use crate::{errors::LovedError, extractors::session::Session, service::Response, state::LovedState};
+
+/// Lists the metrics of every queue, along with when each scheduled task of
+/// the queue processor last ran, and last ran successfully.
+#[get("/")]
+pub async fn index(
+    state: web::Data<LovedState>,
+    session: Session
+) -> impl Responder {
+    ensure_admin(&session)?;
+
+    let (queues, tasks) = state.cache(|con| {
+        let metrics = queues::ALL.iter()
+            .map(|queue| jobs::queue_metrics(con, queue))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((metrics, jobs::task_runs(con)?))
+    }).await?;
+
+    Ok::<_, LovedError>(Response {
+        status: 200,
+        message: None,
+        data: Some(json!({
+            "queues": queues,
+            "tasks": tasks
+        }))
+    })
+}
+
+/// Puts every dead letter of a queue back into it.
+#[post("/{queue}/dead/requeue")]
+pub async fn requeue_dead(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<String>
+) -> impl Responder {
+    ensure_admin(&session)?;
+
+    let queue = find_queue(&path)?;
+    let mut con = state.redis_pool.get_multiplexed_async_connection().await?;
+    let requeued = jobs::requeue_dead_letters(&mut con, queue).await?;
+
+    Ok::<_, LovedError>(Response {
+        status: 200,
+        message: None,
+        data: Some(json!({ "count": requeued }))
+    })
+}
+
+/// Drops every dead letter of a queue.
+#[delete("/{queue}/dead")]
+pub async fn purge_dead(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<String>
+) -> impl Responder {
+    ensure_admin(&session)?;
+
+    let queue = find_queue(&path)?;
+    let purged = state.cache(|con| jobs::purge_dead_letters(con, queue)).await?;
+
+    Ok::<_, LovedError>(Response {
+        status: 200,
+        message: None,
+        data: Some(json!({ "count": purged }))
+    })
+}
+
+fn ensure_admin(session: &Session) -> Result<(), LovedError> {
+    if session.user.has_permission(Permissions::ADMIN) {
+        Ok(())
+    } else {
+        Err(LovedError::Forbidden)
+    }
+}
+
+fn find_queue(name: &str) -> Result<&'static str, LovedError> {
+    queues::find(name).ok_or(LovedError::ModelNotFound { model: "queue" })
+}
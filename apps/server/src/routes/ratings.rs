@@ -0,0 +1,111 @@
+use actix_web::{delete, put, web, Responder};
+use athena::prelude::{ratings::{DisplayRating, FullRating, RatedObject}, reviews::FullSubmissionReview, submissions::FullSubmission};
+use serde::Deserialize;
+use crate::{errors::LovedError, extractors::session::Session, service::Response, state::LovedState};
+
+#[derive(Deserialize)]
+struct RateRequest {
+    value: i16
+}
+
+#[put("/{submission_id}/rating")]
+pub async fn rate_submission(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<i32>,
+    payload: web::Json<RateRequest>,
+) -> impl Responder {
+    let submission = FullSubmission::fetch(path.into_inner(), &state.db_pool).await?;
+
+    if submission.base.submitter_id == session.user.base.id {
+        return Err(LovedError::SelfRating);
+    }
+
+    rate(&state, &session, RatedObject::Submission(submission.base.id), payload.value).await
+}
+
+#[delete("/{submission_id}/rating")]
+pub async fn unrate_submission(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<i32>,
+) -> impl Responder {
+    let submission = FullSubmission::fetch(path.into_inner(), &state.db_pool).await?;
+
+    unrate(&state, &session, RatedObject::Submission(submission.base.id)).await
+}
+
+#[put("/{submission_id}/reviews/{review_id}/rating")]
+pub async fn rate_review(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<(i32, i32)>,
+    payload: web::Json<RateRequest>,
+) -> impl Responder {
+    let review = fetch_review(&state, path.into_inner()).await?;
+
+    if review.base.reviewer_id == session.user.base.id {
+        return Err(LovedError::SelfRating);
+    }
+
+    rate(&state, &session, RatedObject::Review(review.base.id), payload.value).await
+}
+
+#[delete("/{submission_id}/reviews/{review_id}/rating")]
+pub async fn unrate_review(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<(i32, i32)>,
+) -> impl Responder {
+    let review = fetch_review(&state, path.into_inner()).await?;
+
+    unrate(&state, &session, RatedObject::Review(review.base.id)).await
+}
+
+async fn fetch_review(state: &LovedState, (submission_id, review_id): (i32, i32)) -> Result<FullSubmissionReview, LovedError> {
+    let review = FullSubmissionReview::fetch(review_id, &state.db_pool).await?;
+
+    if review.base.submission_id != submission_id {
+        return Err(LovedError::ModelNotFound { model: "review" });
+    }
+
+    Ok(review)
+}
+
+async fn rate(
+    state: &LovedState,
+    session: &Session,
+    object: RatedObject,
+    value: i16
+) -> Result<Response<DisplayRating>, LovedError> {
+    let range = state.settings.rating_range();
+
+    if !range.contains(&value) {
+        return Err(LovedError::InvalidRating { min: *range.start(), max: *range.end() });
+    }
+
+    let rating = FullRating::set(object, session.user.base.id, value, &state.db_pool).await?;
+
+    Ok(Response {
+        status: 200,
+        message: None,
+        data: Some(rating.into_display())
+    })
+}
+
+async fn unrate(
+    state: &LovedState,
+    session: &Session,
+    object: RatedObject
+) -> Result<Response, LovedError> {
+    FullRating::fetch(object, session.user.base.id, &state.db_pool)
+        .await?
+        .delete(&state.db_pool)
+        .await?;
+
+    Ok(Response {
+        status: 200,
+        message: None,
+        data: None
+    })
+}
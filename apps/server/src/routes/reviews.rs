@@ -0,0 +1,169 @@
+use actix_web::{delete, get, patch, post, web, Responder};
+use athena::{errors::AthenaError, entities::submission_reviews, prelude::{reviews::{DisplaySubmissionReview, FullSubmissionReview}, roles::Permissions, submissions::FullSubmission}};
+use sea_orm::{ColumnTrait, EntityTrait, IntoActiveModel, IntoSimpleExpr, Order, QueryFilter, SqlErr};
+use serde::Deserialize;
+use crate::{errors::LovedError, extractors::{pagination::{Cursor, CursorKey, Pagination}, session::Session}, service::Response, state::LovedState};
+
+#[get("/{submission_id}/reviews")]
+pub async fn index(
+    state: web::Data<LovedState>,
+    path: web::Path<i32>,
+    pagination: Pagination<50, DisplaySubmissionReview, true>,
+) -> impl Responder {
+    let submission_id = path.into_inner();
+
+    pagination
+        .provide_with_cursor(|p: &Pagination<50, DisplaySubmissionReview, true>| {
+            let is_cursor = p.is_cursor();
+            let base = submission_reviews::Entity::find()
+                .filter(submission_reviews::Column::SubmissionId.eq(submission_id))
+                .filter(submission_reviews::Column::ParentId.is_null());
+            let query = p.paginate(
+                base.clone(),
+                submission_reviews::Column::Id.into_simple_expr(),
+                submission_reviews::Column::Id,
+                Order::Asc
+            );
+            let db_pool = state.db_pool.clone();
+
+            async move {
+                let total = if is_cursor {
+                    0
+                } else {
+                    FullSubmissionReview::count(&db_pool, |_| base).await?
+                };
+                let reviews = FullSubmissionReview::find(&db_pool, |_| query).await?;
+
+                Ok((reviews.into_iter().map(|r| r.into_display()).collect(), total as usize))
+            }
+        }, |review| Cursor { key: CursorKey::Int(review.id.into()), id: review.id })
+        .await?
+        .respond()
+}
+
+#[derive(Deserialize)]
+struct CreateReviewRequest {
+    content: String,
+    game_mode: Option<i16>,
+    parent_id: Option<i32>
+}
+
+#[post("/{submission_id}/reviews")]
+pub async fn create(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<i32>,
+    payload: web::Json<CreateReviewRequest>,
+) -> impl Responder {
+    let submission = FullSubmission::fetch(path.into_inner(), &state.db_pool).await?;
+    let content = payload.content.trim().to_string();
+
+    if content.is_empty() {
+        return Err(LovedError::BadRequest);
+    }
+
+    // Replies always share the game mode of the review they belong to.
+    let game_mode = if let Some(parent_id) = payload.parent_id {
+        let parent = FullSubmissionReview::fetch(parent_id, &state.db_pool).await?;
+
+        if parent.base.submission_id != submission.base.id {
+            return Err(LovedError::ModelNotFound { model: "review" });
+        }
+
+        parent.base.game_mode
+    } else {
+        payload.game_mode.unwrap_or(submission.base.game_mode)
+    };
+
+    if !(0..=3).contains(&game_mode) {
+        return Err(LovedError::InvalidGameMode);
+    }
+
+    let review = FullSubmissionReview::create(submission_reviews::ActiveModel {
+        id: sea_orm::ActiveValue::NotSet,
+        submission_id: sea_orm::ActiveValue::Set(submission.base.id),
+        reviewer_id: sea_orm::ActiveValue::Set(session.user.base.id),
+        parent_id: sea_orm::ActiveValue::Set(payload.parent_id),
+        game_mode: sea_orm::ActiveValue::Set(game_mode),
+        content: sea_orm::ActiveValue::Set(content)
+    }, &state.db_pool)
+        .await
+        .map_err(|error| match error {
+            // Only top-level reviews are covered by the unique index, replies
+            // never end up here.
+            AthenaError::DbErr(ref err) if matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => LovedError::ReviewExists,
+            error => error.into()
+        })?;
+
+    Ok(Response {
+        status: 200,
+        message: None,
+        data: Some(review.into_display())
+    })
+}
+
+#[derive(Deserialize)]
+struct UpdateReviewRequest {
+    content: String
+}
+
+#[patch("/{submission_id}/reviews/{review_id}")]
+pub async fn update(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<(i32, i32)>,
+    payload: web::Json<UpdateReviewRequest>,
+) -> impl Responder {
+    let review = fetch_editable_review(&state, &session, path.into_inner()).await?;
+    let content = payload.content.trim().to_string();
+
+    if content.is_empty() {
+        return Err(LovedError::BadRequest);
+    }
+
+    let mut model = review.base.into_active_model();
+    model.content = sea_orm::ActiveValue::Set(content);
+
+    Ok(Response {
+        status: 200,
+        message: None,
+        data: Some(FullSubmissionReview::update(model, &state.db_pool).await?.into_display())
+    })
+}
+
+#[delete("/{submission_id}/reviews/{review_id}")]
+pub async fn remove(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<(i32, i32)>,
+) -> impl Responder {
+    let review = fetch_editable_review(&state, &session, path.into_inner()).await?;
+
+    review.delete(&state.db_pool).await?;
+
+    Ok::<_, LovedError>(Response {
+        status: 200,
+        message: None,
+        data: None::<()>
+    })
+}
+
+/// Fetches a review of a submission, making sure the session's user is either
+/// its author or a moderator.
+async fn fetch_editable_review(
+    state: &LovedState,
+    session: &Session,
+    (submission_id, review_id): (i32, i32)
+) -> Result<FullSubmissionReview, LovedError> {
+    let review = FullSubmissionReview::fetch(review_id, &state.db_pool).await?;
+
+    if review.base.submission_id != submission_id {
+        return Err(LovedError::ModelNotFound { model: "review" });
+    }
+
+    if review.base.reviewer_id != session.user.base.id && !session.user.has_permission(Permissions::MANAGE_MODERATION) {
+        return Err(LovedError::Forbidden);
+    }
+
+    Ok(review)
+}
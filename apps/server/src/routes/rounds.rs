@@ -0,0 +1,246 @@
+use actix_web::{get, patch, post, web, Responder};
+use athena::{entities::{rounds, sea_orm_active_enums::RoundStatus}, prelude::{roles::Permissions, rounds::{DisplayRound, FullRound}, users::FullUser}};
+use sea_orm::{prelude::DateTime, EntityTrait, IntoActiveModel, IntoSimpleExpr, Order};
+use serde::{Deserialize, Deserializer};
+use crate::{errors::LovedError, extractors::{pagination::Pagination, session::Session}, service::Response, state::LovedState};
+
+#[get("/")]
+pub async fn index(
+    state: web::Data<LovedState>,
+    session: Session,
+    pagination: Pagination<50, DisplayRound>,
+) -> impl Responder {
+    ensure_permission(&session, Permissions::VIEW_ROUNDS | Permissions::MANAGE_ROUNDS)?;
+
+    pagination
+        .provide(|p: &Pagination<50, DisplayRound>| {
+            let query = p.paginate(
+                rounds::Entity::find(),
+                rounds::Column::Id.into_simple_expr(),
+                rounds::Column::Id,
+                Order::Desc
+            );
+            let db_pool = state.db_pool.clone();
+
+            async move {
+                let total = FullRound::count(&db_pool, |base| base).await?;
+                let rounds = FullRound::find(&db_pool, |_| query).await?;
+
+                Ok((rounds.into_iter().map(|r| r.into_display()).collect(), total as usize))
+            }
+        })
+        .await?
+        .respond()
+}
+
+#[get("/{round_id}")]
+pub async fn show(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<i32>,
+) -> impl Responder {
+    ensure_permission(&session, Permissions::VIEW_ROUNDS | Permissions::MANAGE_ROUNDS)?;
+
+    let round = FullRound::fetch(path.into_inner(), &state.db_pool).await?;
+
+    Ok::<_, LovedError>(Response {
+        status: 200,
+        message: None,
+        data: Some(round.into_display())
+    })
+}
+
+#[derive(Deserialize)]
+struct CaptainSlot {
+    game_mode: i16,
+    captain_id: Option<i32>
+}
+
+#[derive(Deserialize)]
+struct CreateRoundRequest {
+    name: String,
+    #[serde(default)]
+    intro: String,
+    nominations_close_at: Option<DateTime>,
+    voting_starts_at: Option<DateTime>,
+    voting_ends_at: Option<DateTime>,
+    #[serde(default)]
+    captains: Vec<CaptainSlot>
+}
+
+#[post("/")]
+pub async fn create(
+    state: web::Data<LovedState>,
+    session: Session,
+    payload: web::Json<CreateRoundRequest>,
+) -> impl Responder {
+    ensure_permission(&session, Permissions::MANAGE_ROUNDS)?;
+
+    let payload = payload.into_inner();
+    let name = payload.name.trim().to_string();
+
+    if name.is_empty() {
+        return Err(LovedError::BadRequest);
+    }
+
+    validate_dates(payload.nominations_close_at, payload.voting_starts_at, payload.voting_ends_at)?;
+    validate_captains(&state, &payload.captains).await?;
+
+    let round = FullRound::create(rounds::ActiveModel {
+        id: sea_orm::ActiveValue::NotSet,
+        name: sea_orm::ActiveValue::Set(name),
+        intro: sea_orm::ActiveValue::Set(payload.intro),
+        status: sea_orm::ActiveValue::Set(RoundStatus::Planning),
+        nominations_close_at: sea_orm::ActiveValue::Set(payload.nominations_close_at),
+        voting_starts_at: sea_orm::ActiveValue::Set(payload.voting_starts_at),
+        voting_ends_at: sea_orm::ActiveValue::Set(payload.voting_ends_at),
+        created_at: sea_orm::ActiveValue::Set(chrono::Utc::now().naive_utc())
+    }, captain_slots(&payload.captains), &state.db_pool).await?;
+
+    Ok(Response {
+        status: 200,
+        message: None,
+        data: Some(round.into_display())
+    })
+}
+
+/// Dates can be cleared by sending `null`, and are left alone when omitted.
+#[derive(Deserialize)]
+struct UpdateRoundRequest {
+    name: Option<String>,
+    intro: Option<String>,
+    status: Option<RoundStatus>,
+    #[serde(default, deserialize_with = "present")]
+    nominations_close_at: Option<Option<DateTime>>,
+    #[serde(default, deserialize_with = "present")]
+    voting_starts_at: Option<Option<DateTime>>,
+    #[serde(default, deserialize_with = "present")]
+    voting_ends_at: Option<Option<DateTime>>,
+    #[serde(default)]
+    captains: Vec<CaptainSlot>
+}
+
+#[patch("/{round_id}")]
+pub async fn update(
+    state: web::Data<LovedState>,
+    session: Session,
+    path: web::Path<i32>,
+    payload: web::Json<UpdateRoundRequest>,
+) -> impl Responder {
+    ensure_permission(&session, Permissions::MANAGE_ROUNDS)?;
+
+    let payload = payload.into_inner();
+    let round = FullRound::fetch(path.into_inner(), &state.db_pool).await?;
+    let nominations_close_at = payload.nominations_close_at.unwrap_or(round.base.nominations_close_at);
+    let voting_starts_at = payload.voting_starts_at.unwrap_or(round.base.voting_starts_at);
+    let voting_ends_at = payload.voting_ends_at.unwrap_or(round.base.voting_ends_at);
+    let status = payload.status.unwrap_or_else(|| round.base.status.clone());
+
+    validate_dates(nominations_close_at, voting_starts_at, voting_ends_at)?;
+    validate_status(&round.base.status, &status, voting_starts_at, voting_ends_at)?;
+    validate_captains(&state, &payload.captains).await?;
+
+    let mut model = round.base.into_active_model();
+
+    if let Some(name) = payload.name {
+        let name = name.trim().to_string();
+
+        if name.is_empty() {
+            return Err(LovedError::BadRequest);
+        }
+
+        model.name = sea_orm::ActiveValue::Set(name);
+    }
+
+    if let Some(intro) = payload.intro {
+        model.intro = sea_orm::ActiveValue::Set(intro);
+    }
+
+    model.status = sea_orm::ActiveValue::Set(status);
+    model.nominations_close_at = sea_orm::ActiveValue::Set(nominations_close_at);
+    model.voting_starts_at = sea_orm::ActiveValue::Set(voting_starts_at);
+    model.voting_ends_at = sea_orm::ActiveValue::Set(voting_ends_at);
+
+    let round = FullRound::update(model, captain_slots(&payload.captains), &state.db_pool).await?;
+
+    Ok(Response {
+        status: 200,
+        message: None,
+        data: Some(round.into_display())
+    })
+}
+
+/// Tells a `null` field apart from a missing one, which `#[serde(default)]`
+/// leaves as `None`.
+fn present<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+fn ensure_permission(session: &Session, permission: Permissions) -> Result<(), LovedError> {
+    if session.user.has_permission(permission) {
+        Ok(())
+    } else {
+        Err(LovedError::Forbidden)
+    }
+}
+
+/// Makes sure the dates of a round follow each other, ignoring the ones that
+/// aren't set.
+fn validate_dates(
+    nominations_close_at: Option<DateTime>,
+    voting_starts_at: Option<DateTime>,
+    voting_ends_at: Option<DateTime>
+) -> Result<(), LovedError> {
+    let dates: Vec<DateTime> = [nominations_close_at, voting_starts_at, voting_ends_at]
+        .into_iter()
+        .flatten()
+        .collect();
+
+    if dates.windows(2).all(|pair| pair[0] <= pair[1]) {
+        Ok(())
+    } else {
+        Err(LovedError::InvalidRoundDates)
+    }
+}
+
+/// Makes sure a round only moves forward, from planning to voting to done,
+/// once its dates allow it. Rounds past planning must keep their voting dates.
+fn validate_status(
+    current: &RoundStatus,
+    status: &RoundStatus,
+    voting_starts_at: Option<DateTime>,
+    voting_ends_at: Option<DateTime>
+) -> Result<(), LovedError> {
+    let now = chrono::Utc::now().naive_utc();
+    let dated = *status == RoundStatus::Planning || (voting_starts_at.is_some() && voting_ends_at.is_some());
+    let allowed = match (current, status) {
+        (current, status) if current == status => true,
+        (RoundStatus::Planning, RoundStatus::Voting) => voting_starts_at.is_some_and(|starts_at| starts_at <= now),
+        (RoundStatus::Voting, RoundStatus::Done) => voting_ends_at.is_some_and(|ends_at| ends_at <= now),
+        _ => false
+    };
+
+    if dated && allowed {
+        Ok(())
+    } else {
+        Err(LovedError::InvalidRoundStatus)
+    }
+}
+
+fn captain_slots(captains: &[CaptainSlot]) -> impl Iterator<Item = (i16, Option<i32>)> + '_ {
+    captains.iter().map(|slot| (slot.game_mode, slot.captain_id))
+}
+
+async fn validate_captains(state: &LovedState, captains: &[CaptainSlot]) -> Result<(), LovedError> {
+    for slot in captains {
+        if !(0..=3).contains(&slot.game_mode) {
+            return Err(LovedError::InvalidGameMode);
+        }
+
+        if let Some(captain_id) = slot.captain_id {
+            FullUser::fetch(captain_id, &state.db_pool).await?;
+        }
+    }
+
+    Ok(())
+}
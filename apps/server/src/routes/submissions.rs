@@ -1,40 +1,123 @@
+use std::future::IntoFuture;
+
 use actix_web::{get, post, web, Responder};
-use athena::{entities::submissions, prelude::submissions::FullSubmission};
-use sea_orm::{EntityTrait, QueryOrder, QuerySelect};
-use serde_json::json;
-use crate::{extractors::pagination::Pagination, state::LovedState};
+use athena::{entities::submissions, prelude::{beatmaps::FullBeatmapset, submissions::{DisplaySubmission, FullSubmission}}};
+use rosu_v2::{error::OsuError, prelude::RankStatus};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, SqlErr, TransactionTrait};
+use serde::Deserialize;
+use crate::{errors::LovedError, extractors::{filters::SubmissionFilters, pagination::Pagination, session::Session}, service::Response, state::LovedState};
 
 #[get("/")]
 pub async fn index(
     state: web::Data<LovedState>,
-    pagination: Pagination<100>,
+    pagination: Pagination<100, DisplaySubmission, true>,
+    filters: SubmissionFilters,
 ) -> impl Responder {
+    let filters = &filters;
+
     pagination
-        .provide(|p: &Pagination<100>| {
-            let limit = p.limit.try_into().unwrap();
-            let offset = p.get_page_offset().into();
+        .provide_with_cursor(|p: &Pagination<100, DisplaySubmission, true>| {
+            let is_cursor = p.is_cursor();
+            let query = p.paginate(
+                filters.apply(submissions::Entity::find()),
+                filters.sort_key(),
+                submissions::Column::Id,
+                filters.order.clone()
+            );
             let db_pool = state.db_pool.clone();
 
             async move {
-                let submissions = FullSubmission::find(&db_pool, |query| {
-                    query
-                        .order_by_desc(submissions::Column::Id)
-                        .limit(Some(limit))
-                        .offset(Some(offset))
-                })
-                .await?;
-
-                Ok(submissions.into_iter().map(|s| json!({ "hi": 1 })).collect())
+                let total = if is_cursor {
+                    0
+                } else {
+                    FullSubmission::count(&db_pool, |base| filters.apply(base)).await?
+                };
+                let submissions = FullSubmission::find(&db_pool, |_| query).await?;
+
+                state.refresh_stale_users(submissions.iter().map(|submission| &submission.submitter.base)).await;
+
+                Ok((submissions.into_iter().map(|s| s.into_display()).collect(), total as usize))
             }
-        })
+        }, |submission| filters.cursor_of(submission))
         .await?
         .respond()
 }
 
-/*#[post("/")]
+#[derive(Deserialize)]
+struct CreateSubmissionRequest {
+    beatmapset_id: u32,
+    game_mode: i16
+}
+
+#[post("/")]
 pub async fn create(
     state: web::Data<LovedState>,
-    payload: web::Json<FullSubmission>,
+    session: Session,
+    payload: web::Json<CreateSubmissionRequest>,
 ) -> impl Responder {
-    
-}*/
\ No newline at end of file
+    if !(0..=3).contains(&payload.game_mode) {
+        return Err(LovedError::InvalidGameMode);
+    }
+
+    let existing = submissions::Entity::find()
+        .filter(submissions::Column::BeatmapsetId.eq(payload.beatmapset_id as i32))
+        .filter(submissions::Column::GameMode.eq(payload.game_mode))
+        .one(&state.db_pool)
+        .await?;
+
+    if existing.is_some() {
+        return Err(LovedError::SubmissionExists);
+    }
+
+    let beatmapset_id = payload.beatmapset_id;
+    let beatmapset = state.execute_osu(|osu| osu.beatmapset(beatmapset_id).into_future())
+        .await
+        .map_err(|error| match error {
+            OsuError::NotFound => LovedError::ModelNotFound { model: "beatmapset" },
+            error => LovedError::from(error)
+        })?;
+
+    if matches!(beatmapset.status, RankStatus::Ranked | RankStatus::Approved | RankStatus::Loved) {
+        return Err(LovedError::BeatmapsetIneligible);
+    }
+
+    let beatmaps = beatmapset.maps.as_deref().unwrap_or_default();
+
+    if !beatmaps.iter().any(|beatmap| beatmap.mode as i16 == payload.game_mode) {
+        return Err(LovedError::InvalidGameMode);
+    }
+
+    // Every mapper of the set (including guest mappers) has to be stored
+    // before the set itself, as both tables reference their creators. They're
+    // fetched first so that the stored users come with their actual data.
+    let creator_ids = FullBeatmapset::creator_ids(&beatmapset);
+    let creators = state.execute_osu(|osu| osu.users(creator_ids).into_future()).await?;
+
+    // Nothing is kept unless the submission itself goes through.
+    let txn = state.db_pool.begin().await?;
+
+    FullBeatmapset::create_from_osu(&beatmapset, &creators, &txn).await?;
+
+    let base = submissions::ActiveModel {
+        id: sea_orm::ActiveValue::NotSet,
+        beatmapset_id: sea_orm::ActiveValue::Set(beatmapset.mapset_id as i32),
+        submitter_id: sea_orm::ActiveValue::Set(session.user.base.id),
+        game_mode: sea_orm::ActiveValue::Set(payload.game_mode),
+        submitted_at: sea_orm::ActiveValue::Set(chrono::Utc::now().naive_utc())
+    }
+        .insert(&txn)
+        .await
+        .map_err(|error| match error.sql_err() {
+            // Someone else submitted the same set while the API was queried.
+            Some(SqlErr::UniqueConstraintViolation(_)) => LovedError::SubmissionExists,
+            _ => error.into()
+        })?;
+
+    txn.commit().await?;
+
+    Ok(Response {
+        status: 200,
+        message: None,
+        data: Some(FullSubmission::fetch(base.id, &state.db_pool).await?.into_display())
+    })
+}
@@ -0,0 +1,82 @@
+use actix_web::{get, web, Responder};
+use athena::{entities::{user_names, users}, prelude::users::FullUser};
+use sea_orm::{
+    sea_query::{Expr, Func, LikeExpr, Query},
+    ColumnTrait, Condition, EntityTrait, IntoSimpleExpr, Order, QueryFilter
+};
+use serde::Deserialize;
+use crate::{errors::LovedError, extractors::{filters::escape_like, pagination::Pagination}, service::Response, state::LovedState};
+
+#[derive(Deserialize)]
+struct UserSearchRequest {
+    q: Option<String>
+}
+
+#[get("/")]
+pub async fn index(
+    state: web::Data<LovedState>,
+    pagination: Pagination<50>,
+    query: web::Query<UserSearchRequest>,
+) -> impl Responder {
+    let condition = match query.q.as_deref().map(str::trim).filter(|search| !search.is_empty()) {
+        Some(search) => search_condition(search),
+        None => Condition::all()
+    };
+
+    pagination
+        .provide(|p: &Pagination<50>| {
+            let base = users::Entity::find().filter(condition);
+            let query = p.paginate(
+                base.clone(),
+                users::Column::Username.into_simple_expr(),
+                users::Column::Id,
+                Order::Asc
+            );
+            let db_pool = state.db_pool.clone();
+
+            async move {
+                let total = FullUser::count(&db_pool, |_| base).await?;
+                let users = FullUser::find(&db_pool, |_| query).await?;
+
+                state.refresh_stale_users(users.iter().map(|user| &user.base)).await;
+
+                Ok((users.into_iter().map(|u| u.into_display()).collect(), total as usize))
+            }
+        })
+        .await?
+        .respond()
+}
+
+#[get("/{user_id}")]
+pub async fn show(
+    state: web::Data<LovedState>,
+    path: web::Path<i32>,
+) -> impl Responder {
+    let user = FullUser::fetch(path.into_inner(), &state.db_pool).await?;
+
+    state.refresh_stale_users([&user.base]).await;
+
+    Ok::<_, LovedError>(Response {
+        status: 200,
+        message: None,
+        data: Some(user.into_display())
+    })
+}
+
+/// Matches users by their current username, or any they went by before, so
+/// that renamed users can still be found.
+fn search_condition(search: &str) -> Condition {
+    let pattern = LikeExpr::new(format!("%{}%", escape_like(&search.to_lowercase()))).escape('\\');
+
+    Condition::any()
+        .add(Expr::expr(Func::lower(Expr::col((users::Entity, users::Column::Username)))).like(pattern.clone()))
+        .add(
+            users::Column::Id.in_subquery(
+                Query::select()
+                    .column(user_names::Column::UserId)
+                    .from(user_names::Entity)
+                    .and_where(Expr::expr(Func::lower(Expr::col((user_names::Entity, user_names::Column::Username)))).like(pattern))
+                    .to_owned()
+            )
+        )
+}
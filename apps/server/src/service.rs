@@ -1,6 +1,5 @@
 use actix_web::{body::BoxBody, http::header::ContentType, HttpResponse, Responder};
 use serde::Serialize;
-use uuid::Uuid;
 
 fn respond_to_impl<T>(data: &T, _: &actix_web::HttpRequest) -> actix_web::HttpResponse<BoxBody>
 where T : Serialize {
@@ -55,5 +54,5 @@ where
 }
 
 pub fn generate_token() -> String {
-    Uuid::new_v4().to_string() // Generates a new UUID as a token
+    athena::security::generate_token()
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use std::future::Future;
 
-use athena::environment::LovedEnvironment;
+use athena::{entities::users, environment::LovedEnvironment, jobs::{self, UserJob}, security};
+use chrono::{Duration, Utc};
 use rosu_v2::error::OsuError;
 use rosu_v2::prelude::Scopes;
 use rosu_v2::Osu;
@@ -24,6 +25,8 @@ impl LovedState {
     pub async fn new() -> Self {
         // Get the current application environment
         let env = LovedEnvironment::new();
+        security::init(&env.server_secret);
+
         let mut options = ConnectOptions::new(&env.database_url);
 
         options.max_connections(10)
@@ -34,9 +37,9 @@ impl LovedState {
             // ...or really anything.
             OSU_CLIENT.set(
                 Osu::builder()
-                    .url(env.get::<String>("OSU_URL").unwrap())
-                    .client_id(env.get::<u64>("OSU_CLIENT_ID").unwrap())
-                    .client_secret(env.get::<String>("OSU_CLIENT_SECRET").unwrap())
+                    .url(env.osu_url.clone())
+                    .client_id(env.osu_client_id)
+                    .client_secret(env.osu_client_secret.clone())
                     .build()
                     .await
                     .expect("Failed to create osu! API client")
@@ -57,7 +60,7 @@ impl LovedState {
         self.db_pool.execute(stmt).await
     }
     
-    pub async fn execute_osu<T, Fut>(&self, func: impl Fn(&Osu) -> Fut) -> Result<T, OsuError>
+    pub async fn execute_osu<T, Fut>(&self, func: impl FnOnce(&'static Osu) -> Fut) -> Result<T, OsuError>
         where Fut: Future<Output = Result<T, OsuError>>,
     {
         func(OSU_CLIENT.get().unwrap()).await
@@ -68,16 +71,66 @@ impl LovedState {
     }
 
     pub async fn cache<T>(&self, func: impl Fn(&mut redis::Connection) -> Result<T, redis::RedisError>) -> Result<T, redis::RedisError> {
-        let mut con = self.redis_pool.get_connection().unwrap();
+        let mut con = self.redis_pool.get_connection()?;
 
         func(&mut con)
     }
 
+    /// Queues an update for every user whose osu! data is older than
+    /// `USER_REFRESH_HOURS`. Users that were queued recently are left
+    /// alone, and failing to queue never fails the request, it's only logged.
+    pub async fn refresh_stale_users<'a>(&self, users: impl IntoIterator<Item = &'a users::Model>) {
+        let cutoff = Utc::now().naive_utc() - Duration::hours(self.env.user_refresh_hours);
+        let mut stale: Vec<i32> = users.into_iter()
+            .filter(|user| user.api_fetched_at < cutoff)
+            .map(|user| user.id)
+            .collect();
+
+        stale.sort_unstable();
+        stale.dedup();
+
+        if stale.is_empty() {
+            return;
+        }
+
+        let result = async {
+            let mut con = self.redis_pool.get_multiplexed_async_connection().await?;
+            let mut pipe = redis::pipe();
+
+            for user_id in &stale {
+                pipe.cmd("SET")
+                    .arg(jobs::user_update_pending_key(*user_id))
+                    .arg(1)
+                    .arg("NX")
+                    .arg("EX")
+                    .arg(jobs::USER_UPDATE_PENDING_SECONDS);
+            }
+
+            // Only the users whose key didn't exist yet get queued.
+            let pending: Vec<Option<String>> = pipe.query_async(&mut con).await?;
+            let queued: Vec<i32> = stale.iter()
+                .zip(pending)
+                .filter(|(_, pending)| pending.is_some())
+                .map(|(user_id, _)| *user_id)
+                .collect();
+
+            if !queued.is_empty() {
+                jobs::enqueue_async(&mut con, &UserJob::Update { user_ids: queued }).await?;
+            }
+
+            Ok::<_, redis::RedisError>(())
+        }.await;
+
+        if let Err(error) = result {
+            eprintln!("Error queueing user updates: {}", error);
+        }
+    }
+
     pub async fn get_osu_client(&self, user_token: String, oauth_type: &str) -> Result<Osu, OsuError> {
         Osu::builder()
-            .url(self.env.get::<String>("OSU_URL").unwrap())
-            .client_id(self.env.get::<u64>("OSU_CLIENT_ID").unwrap())
-            .client_secret(self.env.get::<String>("OSU_CLIENT_SECRET").unwrap())
+            .url(self.env.osu_url.clone())
+            .client_id(self.env.osu_client_id)
+            .client_secret(self.env.osu_client_secret.clone())
             .with_authorization(user_token, self.env.get::<String>("OSU_REDIRECT_URI").unwrap() + oauth_type, Scopes::default())
             .build()
             .await
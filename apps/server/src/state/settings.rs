@@ -1,21 +1,24 @@
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::env;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json;
 
-#[derive(Serialize, Deserialize)]
-struct LovedSettings {
-}
-
 #[derive(Clone)]
 pub struct LovedSettingsManager {
     settings: HashMap<String, serde_json::Value>,
     settings_path: PathBuf,
 }
 
+impl Default for LovedSettingsManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LovedSettingsManager {
     pub fn new() -> Self {
         let storage_path = env::var("SETTINGS_PATH").unwrap_or_else(|_| "storage".to_string());
@@ -50,6 +53,20 @@ impl LovedSettingsManager {
             .and_then(|value| serde_json::from_value(value.clone()).ok())
     }
 
+    /// The range ratings have to fall into, configurable through the
+    /// `rating_min` and `rating_max` settings. A minimum above the maximum
+    /// would reject every rating, so the defaults are used instead.
+    pub fn rating_range(&self) -> RangeInclusive<i16> {
+        let min = self.read_as("rating_min").unwrap_or(1);
+        let max = self.read_as("rating_max").unwrap_or(5);
+
+        if min > max {
+            return 1..=5;
+        }
+
+        min..=max
+    }
+
     pub fn update(&mut self, key: &str, value: serde_json::Value, is_admin: bool) -> Result<(), String> {
         if !is_admin {
             return Err("Unauthorized: Only admins can update settings.".to_string());
@@ -9,6 +9,15 @@ impl MigratorTrait for Migrator {
             Box::new(m20250227_185441_create_initial_schema::Migration),
             Box::new(m20250309_083034_submissions::Migration),
             Box::new(m20250320_153743_sessions::Migration),
+            Box::new(m20250402_120000_submission_review_uniqueness::Migration),
+            Box::new(m20250405_090000_hash_session_tokens::Migration),
+            Box::new(m20250408_140000_user_names::Migration),
+            Box::new(m20250412_100000_rounds::Migration),
+            Box::new(m20250415_100000_nominations::Migration),
+            Box::new(m20250418_100000_nullable_usernames::Migration),
+            Box::new(m20250418_110000_submission_uniqueness::Migration),
+            Box::new(m20250418_120000_rating_uniqueness::Migration),
+            Box::new(m20250418_130000_rating_objects::Migration),
         ]
     }
 }
@@ -16,3 +25,12 @@ impl MigratorTrait for Migrator {
 mod m20250227_185441_create_initial_schema;
 mod m20250309_083034_submissions;
 mod m20250320_153743_sessions;
+mod m20250402_120000_submission_review_uniqueness;
+mod m20250405_090000_hash_session_tokens;
+mod m20250408_140000_user_names;
+mod m20250412_100000_rounds;
+mod m20250415_100000_nominations;
+mod m20250418_100000_nullable_usernames;
+mod m20250418_110000_submission_uniqueness;
+mod m20250418_120000_rating_uniqueness;
+mod m20250418_130000_rating_objects;
@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The constraint was created inline with the column, so its name was
+        // generated by Postgres. It's looked up by its column instead of
+        // guessing that name.
+        manager
+            .get_connection()
+            .execute_unprepared(r"
+                DO $$
+                DECLARE
+                    unique_constraint name;
+                BEGIN
+                    FOR unique_constraint IN
+                        SELECT con.conname
+                        FROM pg_constraint con
+                        JOIN pg_attribute att ON att.attrelid = con.conrelid AND att.attnum = con.conkey[1]
+                        WHERE con.conrelid = 'submission_reviews'::regclass
+                            AND con.contype = 'u'
+                            AND array_length(con.conkey, 1) = 1
+                            AND att.attname = 'game_mode'
+                    LOOP
+                        EXECUTE format('ALTER TABLE submission_reviews DROP CONSTRAINT %I', unique_constraint);
+                    END LOOP;
+                END
+                $$
+            ")
+            .await?;
+
+        // Replies are left out, so a thread can have as many of them as needed.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_submission_reviews_unique_reviewer")
+                    .table(SubmissionReviews::Table)
+                    .col(SubmissionReviews::SubmissionId)
+                    .col(SubmissionReviews::ReviewerId)
+                    .col(SubmissionReviews::GameMode)
+                    .unique()
+                    .and_where(Expr::col(SubmissionReviews::ParentId).is_null())
+                    .to_owned()
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_submission_reviews_unique_reviewer")
+                    .table(SubmissionReviews::Table)
+                    .to_owned()
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE submission_reviews ADD CONSTRAINT submission_reviews_game_mode_key UNIQUE (game_mode)")
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum SubmissionReviews {
+    Table,
+    SubmissionId,
+    ReviewerId,
+    ParentId,
+    GameMode
+}
@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Session tokens used to be stored as-is. Only their hashes are stored
+        // from now on, so every existing session has to go.
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Sessions::Table)
+                    .to_owned()
+            )
+            .await?;
+
+        // Every authenticated request looks its session up by the hash.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_sessions_session_token")
+                    .table(Sessions::Table)
+                    .col(Sessions::SessionToken)
+                    .unique()
+                    .to_owned()
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The deleted sessions can't be brought back.
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_sessions_session_token")
+                    .table(Sessions::Table)
+                    .to_owned()
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+    Table,
+    SessionToken
+}
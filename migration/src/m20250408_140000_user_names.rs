@@ -0,0 +1,59 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserNames::Table)
+                    .if_not_exists()
+                    .col(pk_auto(UserNames::Id))
+                    .col(integer(UserNames::UserId))
+                    .col(string(UserNames::Username))
+                    .col(timestamp(UserNames::ChangedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_user_names_user")
+                            .from(UserNames::Table, UserNames::UserId)
+                            .to(Users::Table, Users::Id)
+                    )
+                    .to_owned()
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_names_user")
+                    .table(UserNames::Table)
+                    .col(UserNames::UserId)
+                    .to_owned()
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserNames::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserNames {
+    Table,
+    Id,
+    UserId,
+    Username,
+    ChangedAt
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id
+}
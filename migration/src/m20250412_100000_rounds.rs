@@ -0,0 +1,121 @@
+use sea_orm_migration::{prelude::{extension::postgres::Type, *}, schema::*, sea_orm::{ActiveEnum, DbBackend, DeriveActiveEnum, EnumIter, Schema}};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let schema = Schema::new(DbBackend::Postgres);
+
+        manager
+            .create_type(
+                schema.create_enum_from_active_enum::<RoundStatus>()
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Rounds::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Rounds::Id))
+                    .col(string(Rounds::Name))
+                    .col(text(Rounds::Intro))
+                    .col(custom(Rounds::Status, RoundStatus::name()))
+                    .col(timestamp_null(Rounds::NominationsCloseAt))
+                    .col(timestamp_null(Rounds::VotingStartsAt))
+                    .col(timestamp_null(Rounds::VotingEndsAt))
+                    .col(timestamp(Rounds::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned()
+            )
+            .await?;
+
+        // Every round has a slot for each game mode, holding its captain.
+        manager
+            .create_table(
+                Table::create()
+                    .table(RoundGameModes::Table)
+                    .if_not_exists()
+                    .col(integer(RoundGameModes::RoundId))
+                    .col(small_integer(RoundGameModes::GameMode))
+                    .col(integer_null(RoundGameModes::CaptainId))
+                    .primary_key(
+                        Index::create()
+                            .col(RoundGameModes::RoundId)
+                            .col(RoundGameModes::GameMode)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_round_game_modes_round")
+                            .from(RoundGameModes::Table, RoundGameModes::RoundId)
+                            .to(Rounds::Table, Rounds::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_round_game_modes_captain")
+                            .from(RoundGameModes::Table, RoundGameModes::CaptainId)
+                            .to(Users::Table, Users::Id)
+                    )
+                    .to_owned()
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RoundGameModes::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Rounds::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(RoundStatus::name()).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Rounds {
+    Table,
+    Id,
+    Name,
+    Intro,
+    Status,
+    NominationsCloseAt,
+    VotingStartsAt,
+    VotingEndsAt,
+    CreatedAt
+}
+
+#[derive(DeriveIden)]
+pub enum RoundGameModes {
+    Table,
+    RoundId,
+    GameMode,
+    CaptainId
+}
+
+#[derive(EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "round_status")]
+pub enum RoundStatus {
+    #[sea_orm(string_value = "planning")]
+    Planning,
+    #[sea_orm(string_value = "voting")]
+    Voting,
+    #[sea_orm(string_value = "done")]
+    Done
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id
+}
@@ -0,0 +1,192 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Nominations::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Nominations::Id))
+                    .col(integer(Nominations::RoundId))
+                    .col(small_integer(Nominations::GameMode))
+                    .col(integer(Nominations::BeatmapsetId))
+                    .col(integer(Nominations::NominatorId))
+                    .col(integer(Nominations::Position))
+                    .col(text(Nominations::Description))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_nominations_round")
+                            .from(Nominations::Table, Nominations::RoundId)
+                            .to(Rounds::Table, Rounds::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_nominations_beatmapset")
+                            .from(Nominations::Table, Nominations::BeatmapsetId)
+                            .to(Beatmapsets::Table, Beatmapsets::Id)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_nominations_nominator")
+                            .from(Nominations::Table, Nominations::NominatorId)
+                            .to(Users::Table, Users::Id)
+                    )
+                    .to_owned()
+            )
+            .await?;
+
+        // A beatmapset can only be nominated once per game mode of a round.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_nominations_unique_beatmapset")
+                    .table(Nominations::Table)
+                    .col(Nominations::RoundId)
+                    .col(Nominations::GameMode)
+                    .col(Nominations::BeatmapsetId)
+                    .unique()
+                    .to_owned()
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(NominationSubmissions::Table)
+                    .if_not_exists()
+                    .col(integer(NominationSubmissions::NominationId))
+                    .col(integer(NominationSubmissions::SubmissionId))
+                    .primary_key(
+                        Index::create()
+                            .col(NominationSubmissions::NominationId)
+                            .col(NominationSubmissions::SubmissionId)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_nomination_submissions_nomination")
+                            .from(NominationSubmissions::Table, NominationSubmissions::NominationId)
+                            .to(Nominations::Table, Nominations::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_nomination_submissions_submission")
+                            .from(NominationSubmissions::Table, NominationSubmissions::SubmissionId)
+                            .to(Submissions::Table, Submissions::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .to_owned()
+            )
+            .await?;
+
+        // Difficulties of the beatmapset that are left out of the nomination.
+        manager
+            .create_table(
+                Table::create()
+                    .table(NominationExcludedBeatmaps::Table)
+                    .if_not_exists()
+                    .col(integer(NominationExcludedBeatmaps::NominationId))
+                    .col(integer(NominationExcludedBeatmaps::BeatmapId))
+                    .primary_key(
+                        Index::create()
+                            .col(NominationExcludedBeatmaps::NominationId)
+                            .col(NominationExcludedBeatmaps::BeatmapId)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_nomination_excluded_beatmaps_nomination")
+                            .from(NominationExcludedBeatmaps::Table, NominationExcludedBeatmaps::NominationId)
+                            .to(Nominations::Table, Nominations::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_nomination_excluded_beatmaps_beatmap")
+                            .from(NominationExcludedBeatmaps::Table, NominationExcludedBeatmaps::BeatmapId)
+                            .to(Beatmaps::Table, Beatmaps::Id)
+                    )
+                    .to_owned()
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NominationExcludedBeatmaps::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(NominationSubmissions::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Nominations::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Nominations {
+    Table,
+    Id,
+    RoundId,
+    GameMode,
+    BeatmapsetId,
+    NominatorId,
+    Position,
+    Description
+}
+
+#[derive(DeriveIden)]
+pub enum NominationSubmissions {
+    Table,
+    NominationId,
+    SubmissionId
+}
+
+#[derive(DeriveIden)]
+pub enum NominationExcludedBeatmaps {
+    Table,
+    NominationId,
+    BeatmapId
+}
+
+#[derive(DeriveIden)]
+enum Rounds {
+    Table,
+    Id
+}
+
+#[derive(DeriveIden)]
+enum Beatmapsets {
+    Table,
+    Id
+}
+
+#[derive(DeriveIden)]
+enum Beatmaps {
+    Table,
+    Id
+}
+
+#[derive(DeriveIden)]
+enum Submissions {
+    Table,
+    Id
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id
+}
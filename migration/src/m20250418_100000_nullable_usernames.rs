@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .modify_column(ColumnDef::new(Users::Username).string().null())
+                    .to_owned()
+            )
+            .await?;
+
+        // Users the osu! API didn't return used to be stored with an empty
+        // username, which then ended up in their name history once fetched.
+        let conn = manager.get_connection();
+
+        conn.execute_unprepared("UPDATE users SET username = NULL WHERE username = ''").await?;
+        conn.execute_unprepared("DELETE FROM user_names WHERE username = ''").await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("UPDATE users SET username = '' WHERE username IS NULL")
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .modify_column(ColumnDef::new(Users::Username).string().not_null())
+                    .to_owned()
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Username
+}
@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_submissions_unique_beatmapset")
+                    .table(Submissions::Table)
+                    .col(Submissions::BeatmapsetId)
+                    .col(Submissions::GameMode)
+                    .unique()
+                    .to_owned()
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_submissions_unique_beatmapset")
+                    .table(Submissions::Table)
+                    .to_owned()
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Submissions {
+    Table,
+    BeatmapsetId,
+    GameMode
+}
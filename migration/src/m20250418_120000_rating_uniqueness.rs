@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Concurrent ratings could slip past the lookup done before inserting,
+        // so only the latest one of each reviewer is kept.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM ratings a USING ratings b \
+                WHERE a.review_type = b.review_type AND a.object_id = b.object_id \
+                AND a.reviewer_id = b.reviewer_id AND a.id < b.id"
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_ratings_unique_reviewer")
+                    .table(Ratings::Table)
+                    .col(Ratings::ReviewType)
+                    .col(Ratings::ObjectId)
+                    .col(Ratings::ReviewerId)
+                    .unique()
+                    .to_owned()
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_ratings_unique_reviewer")
+                    .table(Ratings::Table)
+                    .to_owned()
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Ratings {
+    Table,
+    ReviewType,
+    ObjectId,
+    ReviewerId
+}
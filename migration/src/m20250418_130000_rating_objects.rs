@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+
+        // `ratings.object_id` points at either a submission or a review, so a
+        // foreign key can't be used; these triggers stand in for one.
+        conn.execute_unprepared(
+            "DELETE FROM ratings WHERE \
+            (review_type = 'submission' AND object_id NOT IN (SELECT id FROM submissions)) OR \
+            (review_type = 'review' AND object_id NOT IN (SELECT id FROM submission_reviews))"
+        ).await?;
+
+        // Locking the object keeps it from being deleted until the rating is
+        // committed, the same way a foreign key would.
+        conn.execute_unprepared(r#"
+            CREATE FUNCTION ratings_check_object() RETURNS trigger AS $$
+            BEGIN
+                IF NEW.review_type = 'submission' THEN
+                    PERFORM 1 FROM submissions WHERE id = NEW.object_id FOR KEY SHARE;
+                ELSE
+                    PERFORM 1 FROM submission_reviews WHERE id = NEW.object_id FOR KEY SHARE;
+                END IF;
+
+                IF NOT FOUND THEN
+                    RAISE EXCEPTION 'rated % % does not exist', NEW.review_type, NEW.object_id
+                        USING ERRCODE = 'foreign_key_violation';
+                END IF;
+
+                RETURN NEW;
+            END;
+            $$ LANGUAGE plpgsql
+        "#).await?;
+
+        conn.execute_unprepared(
+            "CREATE TRIGGER ratings_check_object BEFORE INSERT OR UPDATE OF review_type, object_id ON ratings \
+            FOR EACH ROW EXECUTE FUNCTION ratings_check_object()"
+        ).await?;
+
+        conn.execute_unprepared(r#"
+            CREATE FUNCTION ratings_delete_for_object() RETURNS trigger AS $$
+            BEGIN
+                DELETE FROM ratings WHERE review_type = TG_ARGV[0]::rating_type AND object_id = OLD.id;
+
+                RETURN OLD;
+            END;
+            $$ LANGUAGE plpgsql
+        "#).await?;
+
+        conn.execute_unprepared(
+            "CREATE TRIGGER submissions_delete_ratings AFTER DELETE ON submissions \
+            FOR EACH ROW EXECUTE FUNCTION ratings_delete_for_object('submission')"
+        ).await?;
+
+        conn.execute_unprepared(
+            "CREATE TRIGGER submission_reviews_delete_ratings AFTER DELETE ON submission_reviews \
+            FOR EACH ROW EXECUTE FUNCTION ratings_delete_for_object('review')"
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+
+        conn.execute_unprepared("DROP TRIGGER IF EXISTS submission_reviews_delete_ratings ON submission_reviews").await?;
+        conn.execute_unprepared("DROP TRIGGER IF EXISTS submissions_delete_ratings ON submissions").await?;
+        conn.execute_unprepared("DROP FUNCTION IF EXISTS ratings_delete_for_object").await?;
+        conn.execute_unprepared("DROP TRIGGER IF EXISTS ratings_check_object ON ratings").await?;
+        conn.execute_unprepared("DROP FUNCTION IF EXISTS ratings_check_object").await?;
+
+        Ok(())
+    }
+}
@@ -58,6 +58,7 @@ impl PrimaryKeyTrait for PrimaryKey {
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Relation {
     Beatmapsets,
+    NominationExcludedBeatmaps,
     Users,
 }
 
@@ -88,6 +89,7 @@ impl RelationTrait for Relation {
                 .from(Column::BeatmapsetId)
                 .to(super::beatmapsets::Column::Id)
                 .into(),
+            Self::NominationExcludedBeatmaps => Entity::has_many(super::nomination_excluded_beatmaps::Entity).into(),
             Self::Users => Entity::belongs_to(super::users::Entity)
                 .from(Column::CreatorId)
                 .to(super::users::Column::Id)
@@ -102,6 +104,12 @@ impl Related<super::beatmapsets::Entity> for Entity {
     }
 }
 
+impl Related<super::nomination_excluded_beatmaps::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::NominationExcludedBeatmaps.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
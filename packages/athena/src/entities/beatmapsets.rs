@@ -58,6 +58,7 @@ impl PrimaryKeyTrait for PrimaryKey {
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Relation {
     Beatmaps,
+    Nominations,
     Submissions,
     Users,
 }
@@ -86,6 +87,7 @@ impl RelationTrait for Relation {
     fn def(&self) -> RelationDef {
         match self {
             Self::Beatmaps => Entity::has_many(super::beatmaps::Entity).into(),
+            Self::Nominations => Entity::has_many(super::nominations::Entity).into(),
             Self::Submissions => Entity::has_many(super::submissions::Entity).into(),
             Self::Users => Entity::belongs_to(super::users::Entity)
                 .from(Column::CreatorId)
@@ -101,6 +103,12 @@ impl Related<super::beatmaps::Entity> for Entity {
     }
 }
 
+impl Related<super::nominations::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Nominations.def()
+    }
+}
+
 impl Related<super::submissions::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Submissions.def()
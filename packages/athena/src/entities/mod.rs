@@ -4,11 +4,17 @@ pub mod prelude;
 
 pub mod beatmaps;
 pub mod beatmapsets;
+pub mod nomination_excluded_beatmaps;
+pub mod nomination_submissions;
+pub mod nominations;
 pub mod ratings;
 pub mod role_assignments;
 pub mod roles;
+pub mod round_game_modes;
+pub mod rounds;
 pub mod sea_orm_active_enums;
 pub mod sessions;
 pub mod submission_reviews;
 pub mod submissions;
+pub mod user_names;
 pub mod users;
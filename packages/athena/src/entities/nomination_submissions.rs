@@ -7,52 +7,47 @@ pub struct Entity;
 
 impl EntityName for Entity {
     fn table_name(&self) -> &str {
-        "submission_ratings"
+        "nomination_submissions"
     }
 }
 
 #[derive(Clone, Debug, PartialEq, DeriveModel, DeriveActiveModel, Eq)]
 pub struct Model {
-    pub id: i32,
+    pub nomination_id: i32,
     pub submission_id: i32,
-    pub reviewer_id: i32,
-    pub value: i16,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
 pub enum Column {
-    Id,
+    NominationId,
     SubmissionId,
-    ReviewerId,
-    Value,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
 pub enum PrimaryKey {
-    Id,
+    NominationId,
+    SubmissionId,
 }
 
 impl PrimaryKeyTrait for PrimaryKey {
-    type ValueType = i32;
+    type ValueType = (i32, i32);
     fn auto_increment() -> bool {
-        true
+        false
     }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Relation {
+    Nominations,
     Submissions,
-    Users,
 }
 
 impl ColumnTrait for Column {
     type EntityName = Entity;
     fn def(&self) -> ColumnDef {
         match self {
-            Self::Id => ColumnType::Integer.def(),
+            Self::NominationId => ColumnType::Integer.def(),
             Self::SubmissionId => ColumnType::Integer.def(),
-            Self::ReviewerId => ColumnType::Integer.def(),
-            Self::Value => ColumnType::SmallInteger.def(),
         }
     }
 }
@@ -60,27 +55,27 @@ impl ColumnTrait for Column {
 impl RelationTrait for Relation {
     fn def(&self) -> RelationDef {
         match self {
+            Self::Nominations => Entity::belongs_to(super::nominations::Entity)
+                .from(Column::NominationId)
+                .to(super::nominations::Column::Id)
+                .into(),
             Self::Submissions => Entity::belongs_to(super::submissions::Entity)
                 .from(Column::SubmissionId)
                 .to(super::submissions::Column::Id)
                 .into(),
-            Self::Users => Entity::belongs_to(super::users::Entity)
-                .from(Column::ReviewerId)
-                .to(super::users::Column::Id)
-                .into(),
         }
     }
 }
 
-impl Related<super::submissions::Entity> for Entity {
+impl Related<super::nominations::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::Submissions.def()
+        Relation::Nominations.def()
     }
 }
 
-impl Related<super::users::Entity> for Entity {
+impl Related<super::submissions::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::Users.def()
+        Relation::Submissions.def()
     }
 }
 
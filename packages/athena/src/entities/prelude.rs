@@ -2,10 +2,16 @@
 
 pub use super::beatmaps::Entity as Beatmaps;
 pub use super::beatmapsets::Entity as Beatmapsets;
+pub use super::nomination_excluded_beatmaps::Entity as NominationExcludedBeatmaps;
+pub use super::nomination_submissions::Entity as NominationSubmissions;
+pub use super::nominations::Entity as Nominations;
 pub use super::ratings::Entity as Ratings;
 pub use super::role_assignments::Entity as RoleAssignments;
 pub use super::roles::Entity as Roles;
+pub use super::round_game_modes::Entity as RoundGameModes;
+pub use super::rounds::Entity as Rounds;
 pub use super::sessions::Entity as Sessions;
 pub use super::submission_reviews::Entity as SubmissionReviews;
 pub use super::submissions::Entity as Submissions;
+pub use super::user_names::Entity as UserNames;
 pub use super::users::Entity as Users;
@@ -1,6 +1,7 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.0
 
 use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "rating_type")]
@@ -10,3 +11,15 @@ pub enum RatingType {
     #[sea_orm(string_value = "review")]
     Review,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "round_status")]
+#[serde(rename_all = "snake_case")]
+pub enum RoundStatus {
+    #[sea_orm(string_value = "planning")]
+    Planning,
+    #[sea_orm(string_value = "voting")]
+    Voting,
+    #[sea_orm(string_value = "done")]
+    Done,
+}
@@ -58,7 +58,7 @@ impl ColumnTrait for Column {
             Self::SubmissionId => ColumnType::Integer.def(),
             Self::ReviewerId => ColumnType::Integer.def(),
             Self::ParentId => ColumnType::Integer.def().null(),
-            Self::GameMode => ColumnType::SmallInteger.def().unique(),
+            Self::GameMode => ColumnType::SmallInteger.def(),
             Self::Content => ColumnType::Text.def(),
         }
     }
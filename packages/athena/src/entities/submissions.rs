@@ -44,6 +44,7 @@ impl PrimaryKeyTrait for PrimaryKey {
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Relation {
     Beatmapsets,
+    NominationSubmissions,
     SubmissionReviews,
 }
 
@@ -67,6 +68,7 @@ impl RelationTrait for Relation {
                 .from(Column::BeatmapsetId)
                 .to(super::beatmapsets::Column::Id)
                 .into(),
+            Self::NominationSubmissions => Entity::has_many(super::nomination_submissions::Entity).into(),
             Self::SubmissionReviews => Entity::has_many(super::submission_reviews::Entity).into(),
         }
     }
@@ -78,6 +80,12 @@ impl Related<super::beatmapsets::Entity> for Entity {
     }
 }
 
+impl Related<super::nomination_submissions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::NominationSubmissions.def()
+    }
+}
+
 impl Related<super::submission_reviews::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::SubmissionReviews.def()
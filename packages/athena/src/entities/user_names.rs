@@ -7,24 +7,24 @@ pub struct Entity;
 
 impl EntityName for Entity {
     fn table_name(&self) -> &str {
-        "submission_review_ratings"
+        "user_names"
     }
 }
 
 #[derive(Clone, Debug, PartialEq, DeriveModel, DeriveActiveModel, Eq)]
 pub struct Model {
     pub id: i32,
-    pub review_id: i32,
-    pub reviewer_id: i32,
-    pub value: i16,
+    pub user_id: i32,
+    pub username: String,
+    pub changed_at: DateTime,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
 pub enum Column {
     Id,
-    ReviewId,
-    ReviewerId,
-    Value,
+    UserId,
+    Username,
+    ChangedAt,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -41,7 +41,6 @@ impl PrimaryKeyTrait for PrimaryKey {
 
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Relation {
-    SubmissionReviews,
     Users,
 }
 
@@ -50,9 +49,9 @@ impl ColumnTrait for Column {
     fn def(&self) -> ColumnDef {
         match self {
             Self::Id => ColumnType::Integer.def(),
-            Self::ReviewId => ColumnType::Integer.def(),
-            Self::ReviewerId => ColumnType::Integer.def(),
-            Self::Value => ColumnType::SmallInteger.def(),
+            Self::UserId => ColumnType::Integer.def(),
+            Self::Username => ColumnType::String(StringLen::None).def(),
+            Self::ChangedAt => ColumnType::DateTime.def(),
         }
     }
 }
@@ -60,24 +59,14 @@ impl ColumnTrait for Column {
 impl RelationTrait for Relation {
     fn def(&self) -> RelationDef {
         match self {
-            Self::SubmissionReviews => Entity::belongs_to(super::submission_reviews::Entity)
-                .from(Column::ReviewId)
-                .to(super::submission_reviews::Column::Id)
-                .into(),
             Self::Users => Entity::belongs_to(super::users::Entity)
-                .from(Column::ReviewerId)
+                .from(Column::UserId)
                 .to(super::users::Column::Id)
                 .into(),
         }
     }
 }
 
-impl Related<super::submission_reviews::Entity> for Entity {
-    fn to() -> RelationDef {
-        Relation::SubmissionReviews.def()
-    }
-}
-
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
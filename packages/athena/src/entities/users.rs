@@ -14,7 +14,7 @@ impl EntityName for Entity {
 #[derive(Clone, Debug, PartialEq, DeriveModel, DeriveActiveModel, Eq)]
 pub struct Model {
     pub id: i32,
-    pub username: String,
+    pub username: Option<String>,
     pub country: Option<String>,
     pub restricted: bool,
     pub api_fetched_at: DateTime,
@@ -47,9 +47,12 @@ impl PrimaryKeyTrait for PrimaryKey {
 pub enum Relation {
     Beatmaps,
     Beatmapsets,
+    Nominations,
     Ratings,
     RoleAssignments,
+    RoundGameModes,
     SubmissionReviews,
+    UserNames,
 }
 
 impl ColumnTrait for Column {
@@ -57,7 +60,7 @@ impl ColumnTrait for Column {
     fn def(&self) -> ColumnDef {
         match self {
             Self::Id => ColumnType::Integer.def(),
-            Self::Username => ColumnType::String(StringLen::None).def(),
+            Self::Username => ColumnType::String(StringLen::None).def().null(),
             Self::Country => ColumnType::Char(Some(2u32)).def().null(),
             Self::Restricted => ColumnType::Boolean.def(),
             Self::ApiFetchedAt => ColumnType::DateTime.def(),
@@ -71,9 +74,12 @@ impl RelationTrait for Relation {
         match self {
             Self::Beatmaps => Entity::has_many(super::beatmaps::Entity).into(),
             Self::Beatmapsets => Entity::has_many(super::beatmapsets::Entity).into(),
+            Self::Nominations => Entity::has_many(super::nominations::Entity).into(),
             Self::Ratings => Entity::has_many(super::ratings::Entity).into(),
             Self::RoleAssignments => Entity::has_many(super::role_assignments::Entity).into(),
+            Self::RoundGameModes => Entity::has_many(super::round_game_modes::Entity).into(),
             Self::SubmissionReviews => Entity::has_many(super::submission_reviews::Entity).into(),
+            Self::UserNames => Entity::has_many(super::user_names::Entity).into(),
         }
     }
 }
@@ -90,6 +96,12 @@ impl Related<super::beatmapsets::Entity> for Entity {
     }
 }
 
+impl Related<super::nominations::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Nominations.def()
+    }
+}
+
 impl Related<super::ratings::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Ratings.def()
@@ -102,10 +114,22 @@ impl Related<super::role_assignments::Entity> for Entity {
     }
 }
 
+impl Related<super::round_game_modes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RoundGameModes.def()
+    }
+}
+
 impl Related<super::submission_reviews::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::SubmissionReviews.def()
     }
 }
 
+impl Related<super::user_names::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserNames.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
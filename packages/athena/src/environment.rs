@@ -1,10 +1,19 @@
 use dotenvy::{var, Error};
 use std::{fmt::Debug, str::FromStr};
 
+/// Secrets shorter than this are too easy to guess for hashing tokens with.
+const MIN_SERVER_SECRET_LENGTH: usize = 32;
+
 #[derive(Default, Clone)]
 pub struct LovedEnvironment {
     pub database_url: String,
     pub redis_url: String,
+    pub server_secret: String,
+    pub osu_url: String,
+    pub osu_client_id: u64,
+    pub osu_client_secret: String,
+    /// How old the osu! data of a user can get before it's refreshed.
+    pub user_refresh_hours: i64,
 }
 
 impl LovedEnvironment {
@@ -18,6 +27,15 @@ impl LovedEnvironment {
     pub fn initialize(&mut self) {
         self.database_url = self.get("DATABASE_URL").unwrap();
         self.redis_url = self.get("REDIS_URL").unwrap();
+        self.server_secret = self.get("SERVER_SECRET").expect("SERVER_SECRET must be set");
+        self.osu_url = self.get("OSU_URL").expect("OSU_URL must be set");
+        self.osu_client_id = self.get("OSU_CLIENT_ID").expect("OSU_CLIENT_ID must be set");
+        self.osu_client_secret = self.get("OSU_CLIENT_SECRET").expect("OSU_CLIENT_SECRET must be set");
+        self.user_refresh_hours = self.get_default("USER_REFRESH_HOURS", 24);
+
+        if self.server_secret.len() < MIN_SERVER_SECRET_LENGTH {
+            panic!("SERVER_SECRET must be at least {} characters long", MIN_SERVER_SECRET_LENGTH);
+        }
     }
 
     pub fn get<T: FromStr>(&self, key: &str) -> Result<T, Error>
@@ -1,3 +1,4 @@
+use rosu_v2::error::OsuError;
 use sea_orm::DbErr;
 use thiserror::Error;
 
@@ -7,7 +8,13 @@ pub enum AthenaError {
     DbErr(DbErr),
 
     #[error("Failed to acquire model of type {0}")]
-    ModelNotFound(&'static str)
+    ModelNotFound(&'static str),
+
+    #[error("{0}")]
+    OsuErr(OsuError),
+
+    #[error("The user has no usable osu! tokens")]
+    MissingTokens
 }
 
 impl From<DbErr> for AthenaError {
@@ -15,3 +22,9 @@ impl From<DbErr> for AthenaError {
         AthenaError::DbErr(err)
     }
 }
+
+impl From<OsuError> for AthenaError {
+    fn from(err: OsuError) -> Self {
+        AthenaError::OsuErr(err)
+    }
+}
@@ -0,0 +1,405 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::security;
+
+/// The names of the Redis lists jobs are pushed onto.
+pub mod queues {
+    pub const USER_UPDATE: &str = "loved:queues:user_update";
+    pub const BEATMAPSET_UPDATE: &str = "loved:queues:beatmapset_update";
+
+    /// Every queue, in the order their metrics are listed.
+    pub const ALL: &[&str] = &[USER_UPDATE, BEATMAPSET_UPDATE];
+
+    /// Finds a queue by the part of its name after `loved:queues:`.
+    pub fn find(name: &str) -> Option<&'static str> {
+        ALL.iter()
+            .copied()
+            .find(|queue| queue.strip_prefix("loved:queues:") == Some(name))
+    }
+}
+
+/// How long a user stays marked as queued for an update, in case the update
+/// never gets handled.
+pub const USER_UPDATE_PENDING_SECONDS: u64 = 3600;
+
+/// The key marking a user as queued for an update, so that they aren't queued
+/// again until the update is handled.
+pub fn user_update_pending_key(user_id: i32) -> String {
+    format!("loved:user_update:pending:{}", user_id)
+}
+
+/// The fields of a queue's metrics hash, each counting messages.
+pub mod counters {
+    /// Messages that were handled successfully.
+    pub const PROCESSED: &str = "processed";
+    /// Messages that ran out of attempts and were dead-lettered.
+    pub const FAILED: &str = "failed";
+    /// Failed attempts that were followed by a retry.
+    pub const RETRIED: &str = "retried";
+}
+
+/// Hash of scheduled task names to when they last ran, as a Unix timestamp.
+pub const TASK_RUNS: &str = "loved:tasks:last_run";
+
+/// Hash of scheduled task names to when they last ran without failing, as a
+/// Unix timestamp.
+pub const TASK_SUCCESSES: &str = "loved:tasks:last_success";
+
+/// A payload that goes through one of the queues. Producers and consumers
+/// both go through these types, so that they always agree on the format.
+///
+/// There are no notification jobs yet, as nothing sends notifications. They
+/// get their own queue and job type once something does.
+pub trait Job: Serialize + DeserializeOwned + Send + 'static {
+    /// The queue the job is pushed onto.
+    const QUEUE: &'static str;
+
+    /// Reads a message in the format the queue used before it carried typed
+    /// jobs, which may still be waiting in Redis.
+    fn from_legacy(_message: &str) -> Option<Self> {
+        None
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UserJob {
+    /// Refreshes the osu! data of the users.
+    Update { user_ids: Vec<i32> }
+}
+
+impl Job for UserJob {
+    const QUEUE: &'static str = queues::USER_UPDATE;
+
+    fn from_legacy(message: &str) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct Legacy {
+            user_ids: Vec<i32>
+        }
+
+        serde_json::from_str::<Legacy>(message)
+            .ok()
+            .map(|legacy| UserJob::Update { user_ids: legacy.user_ids })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BeatmapsetJob {
+    /// Refreshes the osu! data of the beatmapsets and their beatmaps.
+    Refresh { beatmapset_ids: Vec<i32> }
+}
+
+impl Job for BeatmapsetJob {
+    const QUEUE: &'static str = queues::BEATMAPSET_UPDATE;
+
+    fn from_legacy(message: &str) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct Legacy {
+            beatmapset_ids: Vec<i32>
+        }
+
+        serde_json::from_str::<Legacy>(message)
+            .ok()
+            .map(|legacy| BeatmapsetJob::Refresh { beatmapset_ids: legacy.beatmapset_ids })
+    }
+}
+
+/// What actually goes through a queue. The ID tells apart messages carrying
+/// the same job, which would otherwise share their attempts.
+#[derive(Serialize, Deserialize)]
+struct Envelope<J> {
+    id: String,
+    job: J
+}
+
+#[derive(Deserialize)]
+struct EnvelopeId {
+    id: String
+}
+
+/// Wraps a job into a message with a fresh ID.
+pub fn encode<J: Job>(job: &J) -> redis::RedisResult<String> {
+    serde_json::to_string(&Envelope { id: security::generate_token(), job })
+        .map_err(|e| (redis::ErrorKind::TypeError, "Failed to serialize job", e.to_string()).into())
+}
+
+/// Reads the job out of a message. Messages queued before they carried an ID
+/// are read as the bare job, and those queued before jobs were typed through
+/// [`Job::from_legacy`].
+pub fn decode<J: Job>(message: &str) -> Result<J, serde_json::Error> {
+    serde_json::from_str::<Envelope<J>>(message)
+        .map(|envelope| envelope.job)
+        .or_else(|_| serde_json::from_str(message))
+        .or_else(|error| J::from_legacy(message).ok_or(error))
+}
+
+/// The ID of a message, if it has one.
+pub fn message_id(message: &str) -> Option<String> {
+    serde_json::from_str::<EnvelopeId>(message)
+        .ok()
+        .map(|envelope| envelope.id)
+}
+
+/// Pushes a job onto its queue.
+pub fn enqueue<J: Job>(con: &mut impl redis::ConnectionLike, job: &J) -> redis::RedisResult<()> {
+    redis::cmd("LPUSH")
+        .arg(J::QUEUE)
+        .arg(encode(job)?)
+        .query(con)
+}
+
+/// Pushes a job onto its queue, over an async connection.
+pub async fn enqueue_async<J: Job>(con: &mut impl redis::aio::ConnectionLike, job: &J) -> redis::RedisResult<()> {
+    redis::cmd("LPUSH")
+        .arg(J::QUEUE)
+        .arg(encode(job)?)
+        .query_async(con)
+        .await
+}
+
+/// Sorted set of delayed job IDs, scored by when they are due in
+/// milliseconds since the epoch.
+const DELAYED_JOBS: &str = "loved:queues:delayed";
+
+/// Hash of delayed job IDs to the job and the queue it goes to.
+const DELAYED_JOB_PAYLOADS: &str = "loved:queues:delayed:jobs";
+
+/// Counter handing out delayed job IDs.
+const DELAYED_JOB_IDS: &str = "loved:queues:delayed:next_id";
+
+#[derive(Serialize, Deserialize)]
+struct DelayedJob {
+    queue: String,
+    payload: String
+}
+
+fn schedule_pipeline(id: u64, queue: &str, payload: String, due_at: DateTime<Utc>) -> redis::Pipeline {
+    let entry = DelayedJob { queue: queue.to_string(), payload };
+    let mut pipe = redis::pipe();
+
+    pipe.atomic()
+        .hset(DELAYED_JOB_PAYLOADS, id, serde_json::to_string(&entry).expect("delayed jobs always serialize"))
+        .ignore()
+        .zadd(DELAYED_JOBS, id, due_at.timestamp_millis())
+        .ignore();
+
+    pipe
+}
+
+/// Pushes a job onto its queue once `due_at` is reached, returning an ID that
+/// can be used to cancel it until then.
+pub fn schedule<J: Job>(con: &mut impl redis::ConnectionLike, job: &J, due_at: DateTime<Utc>) -> redis::RedisResult<u64> {
+    let payload = encode(job)?;
+    let id: u64 = redis::cmd("INCR").arg(DELAYED_JOB_IDS).query(con)?;
+
+    schedule_pipeline(id, J::QUEUE, payload, due_at).query::<()>(con)?;
+    Ok(id)
+}
+
+/// Same as [`schedule`], over an async connection.
+pub async fn schedule_async<J: Job>(con: &mut impl redis::aio::ConnectionLike, job: &J, due_at: DateTime<Utc>) -> redis::RedisResult<u64> {
+    schedule_message_async(con, J::QUEUE, &encode(job)?, due_at).await
+}
+
+/// Same as [`schedule_async`], for a message that was already encoded, such
+/// as one being retried. The message is pushed onto `queue` as is, so it keeps
+/// its ID.
+pub async fn schedule_message_async(
+    con: &mut impl redis::aio::ConnectionLike,
+    queue: &str,
+    message: &str,
+    due_at: DateTime<Utc>
+) -> redis::RedisResult<u64> {
+    let id: u64 = redis::cmd("INCR").arg(DELAYED_JOB_IDS).query_async(con).await?;
+
+    schedule_pipeline(id, queue, message.to_string(), due_at).query_async::<()>(con).await?;
+    Ok(id)
+}
+
+fn cancel_pipeline(id: u64) -> redis::Pipeline {
+    let mut pipe = redis::pipe();
+
+    pipe.atomic()
+        .zrem(DELAYED_JOBS, id)
+        .hdel(DELAYED_JOB_PAYLOADS, id)
+        .ignore();
+
+    pipe
+}
+
+/// Cancels a delayed job, returning whether it was still waiting.
+pub fn cancel(con: &mut impl redis::ConnectionLike, id: u64) -> redis::RedisResult<bool> {
+    let (removed,): (u64,) = cancel_pipeline(id).query(con)?;
+
+    Ok(removed > 0)
+}
+
+/// Same as [`cancel`], over an async connection.
+pub async fn cancel_async(con: &mut impl redis::aio::ConnectionLike, id: u64) -> redis::RedisResult<bool> {
+    let (removed,): (u64,) = cancel_pipeline(id).query_async(con).await?;
+
+    Ok(removed > 0)
+}
+
+/// Moves due delayed jobs onto their queue. Every queue a job may be pushed
+/// onto is passed as a key after the delayed job keys.
+static MOVE_DUE_JOBS: LazyLock<redis::Script> = LazyLock::new(|| redis::Script::new(r"
+    local queues = {}
+
+    for i = 3, #KEYS do
+        queues[KEYS[i]] = true
+    end
+
+    local due = redis.call('ZRANGEBYSCORE', KEYS[1], '-inf', ARGV[1], 'LIMIT', 0, ARGV[2])
+    local moved = 0
+
+    for _, id in ipairs(due) do
+        local entry = redis.call('HGET', KEYS[2], id)
+
+        if entry then
+            local job = cjson.decode(entry)
+
+            if queues[job.queue] then
+                redis.call('LPUSH', job.queue, job.payload)
+                moved = moved + 1
+            end
+        end
+
+        redis.call('ZREM', KEYS[1], id)
+        redis.call('HDEL', KEYS[2], id)
+    end
+
+    return {moved, #due - moved}
+"));
+
+/// Moves up to `limit` delayed jobs that are due onto their queue, returning
+/// how many were moved and how many were dropped. Jobs are only dropped when
+/// their queue isn't one of [`queues::ALL`], which only happens if they were
+/// scheduled by a version with other queues.
+///
+/// This happens in a single script, so a job can't be both moved and
+/// cancelled, or moved twice by concurrent callers.
+pub async fn move_due_jobs(con: &mut impl redis::aio::ConnectionLike, limit: usize) -> redis::RedisResult<(usize, usize)> {
+    let mut invocation = MOVE_DUE_JOBS.prepare_invoke();
+
+    invocation.key(DELAYED_JOBS).key(DELAYED_JOB_PAYLOADS);
+
+    for queue in queues::ALL {
+        invocation.key(*queue);
+    }
+
+    invocation
+        .arg(Utc::now().timestamp_millis())
+        .arg(limit)
+        .invoke_async(con)
+        .await
+}
+
+/// The list messages of a queue are moved to once they run out of attempts.
+pub fn dead_letter_list(queue: &str) -> String {
+    format!("{}:dead", queue)
+}
+
+/// The hash holding the [`counters`] of a queue.
+pub fn metrics_key(queue: &str) -> String {
+    format!("{}:metrics", queue)
+}
+
+#[derive(Serialize, Debug)]
+pub struct QueueMetrics {
+    pub queue: String,
+    /// Messages waiting to be handled.
+    pub pending: u64,
+    pub processed: u64,
+    pub failed: u64,
+    pub retried: u64,
+    /// Messages currently sitting in the dead-letter list.
+    pub dead_letters: u64
+}
+
+pub fn queue_metrics(con: &mut impl redis::ConnectionLike, queue: &str) -> redis::RedisResult<QueueMetrics> {
+    let (counts, pending, dead_letters): (HashMap<String, u64>, u64, u64) = redis::pipe()
+        .hgetall(metrics_key(queue))
+        .llen(queue)
+        .llen(dead_letter_list(queue))
+        .query(con)?;
+    let count = |counter: &str| counts.get(counter).copied().unwrap_or(0);
+
+    Ok(QueueMetrics {
+        queue: queue.to_string(),
+        pending,
+        processed: count(counters::PROCESSED),
+        failed: count(counters::FAILED),
+        retried: count(counters::RETRIED),
+        dead_letters
+    })
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct TaskRuns {
+    /// When the task last ran, whether it failed or not.
+    pub last_run: Option<i64>,
+    /// When the task last ran without failing.
+    pub last_success: Option<i64>
+}
+
+/// When each scheduled task last ran, and last ran successfully, as Unix
+/// timestamps keyed by task name.
+pub fn task_runs(con: &mut impl redis::ConnectionLike) -> redis::RedisResult<HashMap<String, TaskRuns>> {
+    let (runs, successes): (HashMap<String, i64>, HashMap<String, i64>) = redis::pipe()
+        .hgetall(TASK_RUNS)
+        .hgetall(TASK_SUCCESSES)
+        .query(con)?;
+    let mut tasks: HashMap<String, TaskRuns> = HashMap::new();
+
+    for (name, ran_at) in runs {
+        tasks.entry(name).or_default().last_run = Some(ran_at);
+    }
+
+    for (name, succeeded_at) in successes {
+        tasks.entry(name).or_default().last_success = Some(succeeded_at);
+    }
+
+    Ok(tasks)
+}
+
+/// Moves every message of the first list to the end of the second one,
+/// oldest first, then drops the first list.
+static REQUEUE_DEAD_LETTERS: LazyLock<redis::Script> = LazyLock::new(|| redis::Script::new(r"
+    local messages = redis.call('LRANGE', KEYS[1], 0, -1)
+
+    for i = #messages, 1, -1 do
+        redis.call('LPUSH', KEYS[2], messages[i])
+    end
+
+    redis.call('DEL', KEYS[1])
+
+    return #messages
+"));
+
+/// Puts every dead letter of a queue back at the end of it, oldest first,
+/// returning how many were requeued.
+pub async fn requeue_dead_letters(con: &mut impl redis::aio::ConnectionLike, queue: &str) -> redis::RedisResult<u64> {
+    REQUEUE_DEAD_LETTERS
+        .key(dead_letter_list(queue))
+        .key(queue)
+        .invoke_async(con)
+        .await
+}
+
+/// Drops every dead letter of a queue, returning how many were dropped.
+pub fn purge_dead_letters(con: &mut impl redis::ConnectionLike, queue: &str) -> redis::RedisResult<u64> {
+    let dead_letters = dead_letter_list(queue);
+    let (purged,): (u64,) = redis::pipe()
+        .atomic()
+        .llen(&dead_letters)
+        .del(&dead_letters)
+        .ignore()
+        .query(con)?;
+
+    Ok(purged)
+}
@@ -4,6 +4,8 @@ pub mod entities;
 pub mod environment;
 pub mod prelude;
 pub mod errors;
+pub mod security;
+pub mod jobs;
 
 pub trait RequestError {
     fn get_error_code(&self) -> &str;
@@ -1,37 +1,76 @@
+use std::collections::HashMap;
+
 use athena_macros::generate_display;
-use sea_orm::{ActiveModelTrait, DbErr, EntityTrait};
+use rosu_v2::prelude::{BeatmapExtended, BeatmapsetExtended, GameMode, RankStatus, User};
+use sea_orm::{
+    prelude::{DateTime, Decimal}, sea_query::{Expr, OnConflict}, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, Iterable, JoinType,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, TransactionTrait
+};
+use time::OffsetDateTime;
 
-use crate::{entities::{beatmaps, beatmapsets}, errors::AthenaError};
+use crate::{entities::{beatmaps, beatmapsets, users}, errors::AthenaError};
+use super::users::FullUser;
 
 generate_display! {
     #[display(beatmaps::Model)]
     DisplayBeatmap {
-        id = i32: base.id
+        id = i32: base.id,
+        beatmapset_id = i32: base.beatmapset_id,
+        creator_id = i32: base.creator_id,
+        version = String: base.version.clone(),
+        game_mode = i16: base.game_mode,
+        key_count = Option<i16>: base.key_count,
+        bpm = Decimal: base.bpm,
+        star_rating = Decimal: base.star_rating,
+        total_length = i32: base.total_length,
+        play_count = i32: base.play_count,
+        ranked_status = i16: base.ranked_status,
+        deleted_at = Option<DateTime>: base.deleted_at
     }
 }
 
 generate_display! {
     #[display(beatmapsets::Model)]
     DisplayBeatmapset {
-        id = i32: base.id
+        id = i32: base.id,
+        artist = String: base.artist.clone(),
+        title = String: base.title.clone(),
+        creator_id = i32: base.creator_id,
+        creator_name = String: base.creator_name.clone(),
+        favorite_count = i32: base.favorite_count,
+        play_count = i32: base.play_count,
+        ranked_status = i16: base.ranked_status,
+        submitted_at = DateTime: base.submitted_at,
+        updated_at = DateTime: base.updated_at,
+        deleted_at = Option<DateTime>: base.deleted_at
     }
 }
 
+#[derive(Clone)]
 pub struct FullBeatmap {
     pub base: beatmaps::Model,
     pub beatmapset: DisplayBeatmapset
 }
 
 impl FullBeatmap {
-    pub async fn create(beatmap: beatmaps::ActiveModel, conn: &sea_orm::DatabaseConnection) -> Result<Self, DbErr> {
-        let base = beatmap.insert(conn).await?;
+    /// Inserts the beatmap, or updates the existing row if one with the same
+    /// identifier is already stored.
+    pub async fn create(beatmap: beatmaps::ActiveModel, conn: &impl ConnectionTrait) -> Result<Self, DbErr> {
+        let base = beatmaps::Entity::insert(beatmap)
+            .on_conflict(
+                OnConflict::column(beatmaps::Column::Id)
+                    .update_columns(beatmaps::Column::iter().filter(|c| !matches!(c, beatmaps::Column::Id)))
+                    .to_owned()
+            )
+            .exec_with_returning(conn)
+            .await?;
         let beatmapset = DisplayBeatmapset::new(beatmapsets::Entity::find_by_id(base.beatmapset_id).one(conn).await?.unwrap());
         Ok(FullBeatmap { base, beatmapset })
     }
 
-    pub async fn create_all(beatmaps: Vec<beatmaps::ActiveModel>, conn: &sea_orm::DatabaseConnection) -> Result<Vec<Self>, DbErr> {
+    pub async fn create_all(beatmaps: Vec<beatmaps::ActiveModel>, conn: &impl ConnectionTrait) -> Result<Vec<Self>, DbErr> {
         let mut full_beatmaps = Vec::new();
-        
+
         for beatmap in beatmaps {
             full_beatmaps.push(FullBeatmap::create(beatmap, conn).await?);
         }
@@ -51,18 +90,247 @@ impl FullBeatmap {
             Err(AthenaError::ModelNotFound("beatmap"))
         }
     }
+
+    /// Builds an active model out of a beatmap returned by the osu! API.
+    pub fn model_from_osu(beatmap: &BeatmapExtended) -> beatmaps::ActiveModel {
+        beatmaps::ActiveModel {
+            id: sea_orm::ActiveValue::Set(beatmap.map_id as i32),
+            beatmapset_id: sea_orm::ActiveValue::Set(beatmap.mapset_id as i32),
+            bpm: sea_orm::ActiveValue::Set(Decimal::from_f32_retain(beatmap.bpm).unwrap_or_default().round_dp(2)),
+            creator_id: sea_orm::ActiveValue::Set(beatmap.creator_id as i32),
+            deleted_at: sea_orm::ActiveValue::Set(beatmap.deleted_at.map(to_naive)),
+            game_mode: sea_orm::ActiveValue::Set(beatmap.mode as i16),
+            key_count: sea_orm::ActiveValue::Set((beatmap.mode == GameMode::Mania).then_some(beatmap.cs as i16)),
+            play_count: sea_orm::ActiveValue::Set(beatmap.playcount as i32),
+            ranked_status: sea_orm::ActiveValue::Set(beatmap.status as i16),
+            star_rating: sea_orm::ActiveValue::Set(Decimal::from_f32_retain(beatmap.stars).unwrap_or_default().round_dp(2)),
+            total_length: sea_orm::ActiveValue::Set(beatmap.seconds_total as i32),
+            version: sea_orm::ActiveValue::Set(beatmap.version.clone()),
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct FullBeatmapset {
     pub base: beatmapsets::Model,
     pub beatmaps: Vec<FullBeatmap>
 }
 
 impl FullBeatmapset {
-    pub async fn create(beatmapset: beatmapsets::ActiveModel, beatmaps: Vec<beatmaps::ActiveModel>, conn: &sea_orm::DatabaseConnection) -> Result<Self, DbErr> {
-        let base = beatmapset.insert(conn).await?;
+    /// Inserts the beatmapset along with its beatmaps, updating any rows that
+    /// already exist so that re-submitted sets always carry fresh data.
+    pub async fn create(beatmapset: beatmapsets::ActiveModel, beatmaps: Vec<beatmaps::ActiveModel>, conn: &impl ConnectionTrait) -> Result<Self, DbErr> {
+        let base = beatmapsets::Entity::insert(beatmapset)
+            .on_conflict(
+                OnConflict::column(beatmapsets::Column::Id)
+                    .update_columns(beatmapsets::Column::iter().filter(|c| !matches!(c, beatmapsets::Column::Id)))
+                    .to_owned()
+            )
+            .exec_with_returning(conn)
+            .await?;
         let beatmaps = FullBeatmap::create_all(beatmaps, conn).await?;
-        
+
         Ok(FullBeatmapset { base, beatmaps })
     }
+
+    pub async fn fetch(beatmapset_id: i32, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        let base = beatmapsets::Entity::find_by_id(beatmapset_id)
+            .one(conn)
+            .await?;
+
+        if let Some(base) = base {
+            let beatmaps = beatmaps::Entity::find()
+                .filter(beatmaps::Column::BeatmapsetId.eq(base.id))
+                .order_by_asc(beatmaps::Column::GameMode)
+                .order_by_asc(beatmaps::Column::StarRating)
+                .all(conn)
+                .await?
+                .into_iter()
+                .map(|beatmap| FullBeatmap { base: beatmap, beatmapset: DisplayBeatmapset::new(base.clone()) })
+                .collect();
+
+            Ok(FullBeatmapset { base, beatmaps })
+        } else {
+            Err(AthenaError::ModelNotFound("beatmapset"))
+        }
+    }
+
+    /// Fetches several beatmapsets along with their beatmaps at once, keyed
+    /// by their identifier. Beatmapsets that aren't stored are left out.
+    pub async fn fetch_many(beatmapset_ids: &[i32], conn: &sea_orm::DatabaseConnection) -> Result<HashMap<i32, Self>, AthenaError> {
+        let mut beatmapsets: HashMap<i32, Self> = beatmapsets::Entity::find()
+            .filter(beatmapsets::Column::Id.is_in(beatmapset_ids.iter().copied()))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|base| (base.id, FullBeatmapset { base, beatmaps: Vec::new() }))
+            .collect();
+
+        let beatmaps = beatmaps::Entity::find()
+            .filter(beatmaps::Column::BeatmapsetId.is_in(beatmapset_ids.iter().copied()))
+            .order_by_asc(beatmaps::Column::GameMode)
+            .order_by_asc(beatmaps::Column::StarRating)
+            .all(conn)
+            .await?;
+
+        for beatmap in beatmaps {
+            if let Some(beatmapset) = beatmapsets.get_mut(&beatmap.beatmapset_id) {
+                let display = DisplayBeatmapset::new(beatmapset.base.clone());
+
+                beatmapset.beatmaps.push(FullBeatmap { base: beatmap, beatmapset: display });
+            }
+        }
+
+        Ok(beatmapsets)
+    }
+
+    pub fn into_display(self) -> DisplayBeatmapset {
+        DisplayBeatmapset::new(self.base)
+    }
+
+    /// Stores a beatmapset returned by the osu! API along with its beatmaps and
+    /// any of its mappers that aren't stored yet. `creators` are the mappers
+    /// the API returned for [`FullBeatmapset::creator_ids`].
+    ///
+    /// This takes several writes, so callers should run it in a transaction.
+    pub async fn create_from_osu(beatmapset: &BeatmapsetExtended, creators: &[User], conn: &impl ConnectionTrait) -> Result<Self, AthenaError> {
+        FullBeatmapset::create_missing_creators(beatmapset, creators, conn).await?;
+
+        let beatmaps = beatmapset.maps
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(FullBeatmap::model_from_osu)
+            .collect();
+
+        Ok(FullBeatmapset::create(FullBeatmapset::model_from_osu(beatmapset), beatmaps, conn).await?)
+    }
+
+    /// Refreshes a stored beatmapset from the osu! API. Difficulties that were
+    /// removed from the set since are marked as deleted.
+    pub async fn update_from_osu(beatmapset: &BeatmapsetExtended, creators: &[User], conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        let txn = conn.begin().await?;
+        let full_beatmapset = FullBeatmapset::create_from_osu(beatmapset, creators, &txn).await?;
+        let map_ids: Vec<i32> = full_beatmapset.beatmaps.iter().map(|beatmap| beatmap.base.id).collect();
+
+        beatmaps::Entity::update_many()
+            .col_expr(beatmaps::Column::DeletedAt, Expr::value(chrono::Utc::now().naive_utc()))
+            .filter(beatmaps::Column::BeatmapsetId.eq(full_beatmapset.base.id))
+            .filter(beatmaps::Column::Id.is_not_in(map_ids))
+            .filter(beatmaps::Column::DeletedAt.is_null())
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+        FullBeatmapset::fetch(full_beatmapset.base.id, conn).await
+    }
+
+    /// The mappers of the set, guest mappers included, without duplicates.
+    pub fn creator_ids(beatmapset: &BeatmapsetExtended) -> Vec<u32> {
+        let mut creator_ids: Vec<u32> = beatmapset.maps
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|beatmap| beatmap.creator_id)
+            .collect();
+
+        creator_ids.push(beatmapset.creator_id);
+        creator_ids.sort_unstable();
+        creator_ids.dedup();
+
+        creator_ids
+    }
+
+    /// Stores the mappers of the set that aren't stored yet, as the set and
+    /// its beatmaps reference them.
+    async fn create_missing_creators(beatmapset: &BeatmapsetExtended, creators: &[User], conn: &impl ConnectionTrait) -> Result<(), AthenaError> {
+        let creators = FullBeatmapset::creator_ids(beatmapset)
+            .into_iter()
+            .map(|creator_id| {
+                let creator = creators.iter().find(|user| user.user_id == creator_id);
+
+                // Users that the API doesn't return are most likely restricted, so
+                // store what we know and leave them to be refreshed later. Their
+                // username stays unknown unless the set tells us about it.
+                let username = match creator {
+                    Some(creator) => Some(creator.username.to_string()),
+                    None if creator_id == beatmapset.creator_id => Some(beatmapset.creator_name.to_string()),
+                    None => None
+                };
+
+                users::ActiveModel {
+                    id: sea_orm::ActiveValue::Set(creator_id as i32),
+                    username: sea_orm::ActiveValue::Set(username),
+                    country: sea_orm::ActiveValue::Set(creator.map(|creator| creator.country_code.to_string())),
+                    restricted: sea_orm::ActiveValue::Set(creator.is_none()),
+                    api_fetched_at: sea_orm::ActiveValue::Set(if creator.is_some() {
+                        chrono::Utc::now().naive_utc()
+                    } else {
+                        chrono::NaiveDateTime::default()
+                    }),
+                    tokens: sea_orm::ActiveValue::Set(serde_json::json!({}))
+                }
+            })
+            .collect();
+
+        FullUser::create_missing(creators, conn).await
+    }
+
+    /// Marks a beatmapset that the osu! API no longer returns as deleted.
+    pub async fn mark_deleted(beatmapset_id: i32, conn: &sea_orm::DatabaseConnection) -> Result<(), AthenaError> {
+        let now = chrono::Utc::now().naive_utc();
+
+        beatmapsets::Entity::update_many()
+            .col_expr(beatmapsets::Column::DeletedAt, Expr::value(now))
+            .col_expr(beatmapsets::Column::ApiFetchedAt, Expr::value(now))
+            .filter(beatmapsets::Column::Id.eq(beatmapset_id))
+            .filter(beatmapsets::Column::DeletedAt.is_null())
+            .exec(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Finds the beatmapsets of open submissions, meaning ones that are still
+    /// eligible for Loved, that were last fetched before `fetched_before`.
+    pub async fn find_stale_submitted(fetched_before: DateTime, conn: &sea_orm::DatabaseConnection) -> Result<Vec<i32>, AthenaError> {
+        let closed = [RankStatus::Ranked, RankStatus::Approved, RankStatus::Loved].map(|status| status as i16);
+
+        Ok(beatmapsets::Entity::find()
+            .select_only()
+            .column(beatmapsets::Column::Id)
+            .distinct()
+            .join(JoinType::InnerJoin, beatmapsets::Relation::Submissions.def())
+            .filter(beatmapsets::Column::ApiFetchedAt.lt(fetched_before))
+            .filter(beatmapsets::Column::DeletedAt.is_null())
+            .filter(beatmapsets::Column::RankedStatus.is_not_in(closed))
+            .into_tuple::<i32>()
+            .all(conn)
+            .await?)
+    }
+
+    /// Builds an active model out of a beatmapset returned by the osu! API.
+    /// The beatmaps of the set are not included.
+    pub fn model_from_osu(beatmapset: &BeatmapsetExtended) -> beatmapsets::ActiveModel {
+        beatmapsets::ActiveModel {
+            id: sea_orm::ActiveValue::Set(beatmapset.mapset_id as i32),
+            api_fetched_at: sea_orm::ActiveValue::Set(chrono::Utc::now().naive_utc()),
+            artist: sea_orm::ActiveValue::Set(beatmapset.artist.clone()),
+            creator_id: sea_orm::ActiveValue::Set(beatmapset.creator_id as i32),
+            creator_name: sea_orm::ActiveValue::Set(beatmapset.creator_name.to_string()),
+            deleted_at: sea_orm::ActiveValue::Set(None),
+            favorite_count: sea_orm::ActiveValue::Set(beatmapset.favourite_count as i32),
+            play_count: sea_orm::ActiveValue::Set(beatmapset.playcount as i32),
+            ranked_status: sea_orm::ActiveValue::Set(beatmapset.status as i16),
+            submitted_at: sea_orm::ActiveValue::Set(to_naive(beatmapset.submitted_date.unwrap_or(beatmapset.last_updated))),
+            title: sea_orm::ActiveValue::Set(beatmapset.title.clone()),
+            updated_at: sea_orm::ActiveValue::Set(to_naive(beatmapset.last_updated)),
+        }
+    }
+}
+
+fn to_naive(date: OffsetDateTime) -> chrono::NaiveDateTime {
+    chrono::DateTime::from_timestamp(date.unix_timestamp(), date.nanosecond())
+        .unwrap_or_default()
+        .naive_utc()
 }
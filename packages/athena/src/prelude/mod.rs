@@ -1,10 +1,18 @@
 use std::future::Future;
 
+use crate::errors::AthenaError;
+
 pub mod users;
 pub mod roles;
 pub mod submissions;
 pub mod beatmaps;
+pub mod reviews;
+pub mod ratings;
+pub mod sessions;
+pub mod tokens;
+pub mod rounds;
+pub mod nominations;
 
 pub trait AsyncFromDatabase<T>: Sized {
-    fn from_async(value: T, conn: &sea_orm::DatabaseConnection) -> impl Future<Output = Self>;
+    fn from_async(value: T, conn: &sea_orm::DatabaseConnection) -> impl Future<Output = Result<Self, AthenaError>>;
 }
\ No newline at end of file
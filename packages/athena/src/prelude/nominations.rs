@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use athena_macros::generate_display;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, QueryFilter, QueryOrder, QuerySelect, Select, TransactionTrait};
+use crate::{entities::{nomination_excluded_beatmaps, nomination_submissions, nominations, rounds}, errors::AthenaError};
+use super::{beatmaps::{DisplayBeatmap, DisplayBeatmapset, FullBeatmapset}, users::FullUser};
+
+generate_display! {
+    #[display(nominations::Model)]
+    DisplayNomination {
+        id = i32: base.id,
+        round_id = i32: base.round_id,
+        game_mode = i16: base.game_mode,
+        position = i32: base.position,
+        description = String: base.description.clone(),
+        nominator = serde_json::Value: serde_json::Value::Null,
+        beatmapset = Option<DisplayBeatmapset>: None,
+        beatmaps = Vec<DisplayBeatmap>: Vec::new(),
+        excluded_beatmap_ids = Vec<i32>: Vec::new(),
+        submission_ids = Vec<i32>: Vec::new()
+    }
+}
+
+/// A beatmapset picked into a round for one of its game modes, along with the
+/// submissions that led to it.
+pub struct FullNomination {
+    pub base: nominations::Model,
+    pub nominator: FullUser,
+    pub beatmapset: FullBeatmapset,
+    pub submission_ids: Vec<i32>,
+    pub excluded_beatmap_ids: Vec<i32>
+}
+
+impl FullNomination {
+    /// Creates a nomination at the end of its game mode's order, which is
+    /// why its position is left for this to fill in. Its round and game mode
+    /// have to be set.
+    pub async fn create(
+        mut nomination: nominations::ActiveModel,
+        submission_ids: Vec<i32>,
+        excluded_beatmap_ids: Vec<i32>,
+        conn: &sea_orm::DatabaseConnection
+    ) -> Result<Self, AthenaError> {
+        let round_id = nomination.round_id.clone().unwrap();
+        let game_mode = nomination.game_mode.clone().unwrap();
+        let txn = conn.begin().await?;
+
+        // Locking the round keeps concurrent nominations from taking the same
+        // position.
+        rounds::Entity::find_by_id(round_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(AthenaError::ModelNotFound("round"))?;
+
+        let last_position = nominations::Entity::find()
+            .select_only()
+            .column_as(nominations::Column::Position.max(), "position")
+            .filter(nominations::Column::RoundId.eq(round_id))
+            .filter(nominations::Column::GameMode.eq(game_mode))
+            .into_tuple::<Option<i32>>()
+            .one(&txn)
+            .await?
+            .flatten();
+
+        nomination.position = sea_orm::ActiveValue::Set(last_position.map_or(0, |position| position + 1));
+
+        let base = nomination.insert(&txn).await?;
+
+        if !submission_ids.is_empty() {
+            nomination_submissions::Entity::insert_many(submission_ids.into_iter().map(|submission_id| nomination_submissions::ActiveModel {
+                nomination_id: sea_orm::ActiveValue::Set(base.id),
+                submission_id: sea_orm::ActiveValue::Set(submission_id)
+            }))
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        if !excluded_beatmap_ids.is_empty() {
+            nomination_excluded_beatmaps::Entity::insert_many(excluded_beatmap_ids.into_iter().map(|beatmap_id| nomination_excluded_beatmaps::ActiveModel {
+                nomination_id: sea_orm::ActiveValue::Set(base.id),
+                beatmap_id: sea_orm::ActiveValue::Set(beatmap_id)
+            }))
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+        FullNomination::load(base, conn).await
+    }
+
+    pub async fn fetch(nomination_id: i32, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        let base = nominations::Entity::find_by_id(nomination_id)
+            .one(conn)
+            .await?;
+
+        if let Some(base) = base {
+            FullNomination::load(base, conn).await
+        } else {
+            Err(AthenaError::ModelNotFound("nomination"))
+        }
+    }
+
+    pub async fn find(
+        conn: &sea_orm::DatabaseConnection,
+        fun: impl FnOnce(Select<nominations::Entity>) -> Select<nominations::Entity>,
+    ) -> Result<Vec<Self>, AthenaError> {
+        let base = nominations::Entity::find();
+        let base = fun(base).all(conn).await?;
+
+        FullNomination::load_many(base, conn).await
+    }
+
+    /// The IDs of a round's nominations for a game mode, in order.
+    pub async fn ids_in_order(round_id: i32, game_mode: i16, conn: &sea_orm::DatabaseConnection) -> Result<Vec<i32>, AthenaError> {
+        Ok(nominations::Entity::find()
+            .select_only()
+            .column(nominations::Column::Id)
+            .filter(nominations::Column::RoundId.eq(round_id))
+            .filter(nominations::Column::GameMode.eq(game_mode))
+            .order_by_asc(nominations::Column::Position)
+            .order_by_asc(nominations::Column::Id)
+            .into_tuple::<i32>()
+            .all(conn)
+            .await?)
+    }
+
+    /// Gives the nominations the positions they have in `nomination_ids`.
+    pub async fn reorder(nomination_ids: &[i32], conn: &sea_orm::DatabaseConnection) -> Result<(), AthenaError> {
+        let txn = conn.begin().await?;
+
+        for (position, nomination_id) in nomination_ids.iter().enumerate() {
+            nominations::ActiveModel {
+                id: sea_orm::ActiveValue::Unchanged(*nomination_id),
+                position: sea_orm::ActiveValue::Set(position as i32),
+                ..Default::default()
+            }.update(&txn).await?;
+        }
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    pub async fn delete(self, conn: &sea_orm::DatabaseConnection) -> Result<(), AthenaError> {
+        self.base.delete(conn).await?;
+
+        Ok(())
+    }
+
+    pub fn into_display(self) -> DisplayNomination {
+        let mut display = DisplayNomination::new(self.base);
+
+        display.nominator = self.nominator.into_display();
+        display.beatmaps = self.beatmapset.beatmaps
+            .into_iter()
+            .filter(|beatmap| beatmap.base.game_mode == display.game_mode)
+            .filter(|beatmap| !self.excluded_beatmap_ids.contains(&beatmap.base.id))
+            .map(|beatmap| DisplayBeatmap::new(beatmap.base))
+            .collect();
+        display.beatmapset = Some(DisplayBeatmapset::new(self.beatmapset.base));
+        display.excluded_beatmap_ids = self.excluded_beatmap_ids;
+        display.submission_ids = self.submission_ids;
+
+        display
+    }
+
+    async fn load(base: nominations::Model, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        Ok(FullNomination::load_many(vec![base], conn).await?.remove(0))
+    }
+
+    /// Loads what every nomination points at with a query per table, rather
+    /// than a few per nomination.
+    async fn load_many(base: Vec<nominations::Model>, conn: &sea_orm::DatabaseConnection) -> Result<Vec<Self>, AthenaError> {
+        let nomination_ids: Vec<i32> = base.iter().map(|nomination| nomination.id).collect();
+        let nominator_ids: Vec<i32> = base.iter().map(|nomination| nomination.nominator_id).collect();
+        let beatmapset_ids: Vec<i32> = base.iter().map(|nomination| nomination.beatmapset_id).collect();
+
+        let nominators = FullUser::fetch_many(&nominator_ids, conn).await?;
+        let beatmapsets = FullBeatmapset::fetch_many(&beatmapset_ids, conn).await?;
+        let mut submission_ids: HashMap<i32, Vec<i32>> = HashMap::new();
+        let mut excluded_beatmap_ids: HashMap<i32, Vec<i32>> = HashMap::new();
+
+        let links = nomination_submissions::Entity::find()
+            .filter(nomination_submissions::Column::NominationId.is_in(nomination_ids.iter().copied()))
+            .all(conn)
+            .await?;
+
+        for link in links {
+            submission_ids.entry(link.nomination_id).or_default().push(link.submission_id);
+        }
+
+        let exclusions = nomination_excluded_beatmaps::Entity::find()
+            .filter(nomination_excluded_beatmaps::Column::NominationId.is_in(nomination_ids))
+            .all(conn)
+            .await?;
+
+        for exclusion in exclusions {
+            excluded_beatmap_ids.entry(exclusion.nomination_id).or_default().push(exclusion.beatmap_id);
+        }
+
+        // Nominators and sets are shared between nominations, hence the clones.
+        base.into_iter().map(|base| {
+            let nominator = nominators.get(&base.nominator_id).cloned().ok_or(AthenaError::ModelNotFound("user"))?;
+            let beatmapset = beatmapsets.get(&base.beatmapset_id).cloned().ok_or(AthenaError::ModelNotFound("beatmapset"))?;
+
+            Ok(FullNomination {
+                submission_ids: submission_ids.remove(&base.id).unwrap_or_default(),
+                excluded_beatmap_ids: excluded_beatmap_ids.remove(&base.id).unwrap_or_default(),
+                base,
+                nominator,
+                beatmapset
+            })
+        }).collect()
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use athena_macros::generate_display;
+use sea_orm::{sea_query::OnConflict, ColumnTrait, EntityTrait, ModelTrait, QueryFilter, QuerySelect, SqlErr};
+use crate::{entities::{ratings, sea_orm_active_enums::RatingType}, errors::AthenaError};
+
+generate_display! {
+    #[display(ratings::Model)]
+    DisplayRating {
+        id = i32: base.id,
+        object_id = i32: base.object_id,
+        reviewer_id = i32: base.reviewer_id,
+        value = i16: base.value
+    }
+}
+
+/// An object that can be rated. The `ratings` table points at either kind
+/// through the same column, so triggers make sure the object exists and take
+/// its ratings along when it's deleted.
+#[derive(Clone, Copy)]
+pub enum RatedObject {
+    Submission(i32),
+    Review(i32)
+}
+
+impl RatedObject {
+    pub fn rating_type(&self) -> RatingType {
+        match self {
+            RatedObject::Submission(_) => RatingType::Submission,
+            RatedObject::Review(_) => RatingType::Review
+        }
+    }
+
+    pub fn id(&self) -> i32 {
+        match self {
+            RatedObject::Submission(id) | RatedObject::Review(id) => *id
+        }
+    }
+
+    pub fn model_name(&self) -> &'static str {
+        match self {
+            RatedObject::Submission(_) => "submission",
+            RatedObject::Review(_) => "review"
+        }
+    }
+}
+
+/// The amount of ratings an object received, along with their sum and
+/// average value.
+#[derive(Default)]
+pub struct RatingSummary {
+    pub count: u64,
+    pub sum: i64,
+    pub average: Option<f64>
+}
+
+impl RatingSummary {
+    fn new(count: i64, sum: Option<i64>) -> Self {
+        RatingSummary {
+            count: count as u64,
+            sum: sum.unwrap_or(0),
+            average: sum
+                .filter(|_| count > 0)
+                .map(|sum| sum as f64 / count as f64)
+        }
+    }
+}
+
+pub struct FullRating {
+    pub base: ratings::Model
+}
+
+impl FullRating {
+    /// Fetches the rating a user gave to an object.
+    pub async fn fetch(
+        object: RatedObject,
+        reviewer_id: i32,
+        conn: &sea_orm::DatabaseConnection
+    ) -> Result<Self, AthenaError> {
+        let base = ratings::Entity::find()
+            .filter(ratings::Column::ReviewType.eq(object.rating_type()))
+            .filter(ratings::Column::ObjectId.eq(object.id()))
+            .filter(ratings::Column::ReviewerId.eq(reviewer_id))
+            .one(conn)
+            .await?;
+
+        if let Some(base) = base {
+            Ok(FullRating { base })
+        } else {
+            Err(AthenaError::ModelNotFound("rating"))
+        }
+    }
+
+    /// Casts a user's rating on an object, or changes it if they already
+    /// rated it.
+    pub async fn set(
+        object: RatedObject,
+        reviewer_id: i32,
+        value: i16,
+        conn: &sea_orm::DatabaseConnection
+    ) -> Result<Self, AthenaError> {
+        let base = ratings::Entity::insert(ratings::ActiveModel {
+            id: sea_orm::ActiveValue::NotSet,
+            review_type: sea_orm::ActiveValue::Set(object.rating_type()),
+            object_id: sea_orm::ActiveValue::Set(object.id()),
+            reviewer_id: sea_orm::ActiveValue::Set(reviewer_id),
+            value: sea_orm::ActiveValue::Set(value)
+        })
+            .on_conflict(
+                OnConflict::columns([ratings::Column::ReviewType, ratings::Column::ObjectId, ratings::Column::ReviewerId])
+                    .update_column(ratings::Column::Value)
+                    .to_owned()
+            )
+            .exec_with_returning(conn)
+            .await
+            .map_err(|error| match error.sql_err() {
+                // Raised by the trigger when the object doesn't exist.
+                Some(SqlErr::ForeignKeyConstraintViolation(_)) => AthenaError::ModelNotFound(object.model_name()),
+                _ => AthenaError::DbErr(error)
+            })?;
+
+        Ok(FullRating { base })
+    }
+
+    pub async fn delete(self, conn: &sea_orm::DatabaseConnection) -> Result<(), AthenaError> {
+        self.base.delete(conn).await?;
+
+        Ok(())
+    }
+
+    /// Aggregates every rating given to an object.
+    pub async fn summarize(
+        object: RatedObject,
+        conn: &sea_orm::DatabaseConnection
+    ) -> Result<RatingSummary, AthenaError> {
+        let (count, sum) = ratings::Entity::find()
+            .select_only()
+            .column_as(ratings::Column::Id.count(), "count")
+            .column_as(ratings::Column::Value.sum(), "sum")
+            .filter(ratings::Column::ReviewType.eq(object.rating_type()))
+            .filter(ratings::Column::ObjectId.eq(object.id()))
+            .into_tuple::<(i64, Option<i64>)>()
+            .one(conn)
+            .await?
+            .unwrap_or((0, None));
+
+        Ok(RatingSummary::new(count, sum))
+    }
+
+    /// Aggregates the ratings given to several objects of the same type at
+    /// once, keyed by the object's identifier. Unrated objects are left out.
+    pub async fn summarize_many(
+        rating_type: RatingType,
+        object_ids: &[i32],
+        conn: &sea_orm::DatabaseConnection
+    ) -> Result<HashMap<i32, RatingSummary>, AthenaError> {
+        let summaries = ratings::Entity::find()
+            .select_only()
+            .column(ratings::Column::ObjectId)
+            .column_as(ratings::Column::Id.count(), "count")
+            .column_as(ratings::Column::Value.sum(), "sum")
+            .filter(ratings::Column::ReviewType.eq(rating_type))
+            .filter(ratings::Column::ObjectId.is_in(object_ids.iter().copied()))
+            .group_by(ratings::Column::ObjectId)
+            .into_tuple::<(i32, i64, Option<i64>)>()
+            .all(conn)
+            .await?;
+
+        Ok(summaries
+            .into_iter()
+            .map(|(object_id, count, sum)| (object_id, RatingSummary::new(count, sum)))
+            .collect())
+    }
+
+    pub fn into_display(self) -> DisplayRating {
+        DisplayRating::new(self.base)
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use athena_macros::generate_display;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Select, TransactionTrait};
+use crate::{entities::{sea_orm_active_enums::RatingType, submission_reviews}, errors::AthenaError};
+use super::{ratings::{FullRating, RatingSummary}, users::FullUser};
+
+generate_display! {
+    #[display(submission_reviews::Model)]
+    DisplaySubmissionReview {
+        id = i32: base.id,
+        submission_id = i32: base.submission_id,
+        parent_id = Option<i32>: base.parent_id,
+        game_mode = i16: base.game_mode,
+        content = String: base.content.clone(),
+        reviewer = serde_json::Value: serde_json::Value::Null,
+        rating_count = u64: 0,
+        average_rating = Option<f64>: None,
+        replies = Vec<DisplaySubmissionReview>: Vec::new()
+    }
+}
+
+pub struct FullSubmissionReview {
+    pub base: submission_reviews::Model,
+    pub reviewer: FullUser,
+    pub ratings: RatingSummary,
+    pub replies: Vec<FullSubmissionReview>
+}
+
+impl FullSubmissionReview {
+    pub async fn create(review: submission_reviews::ActiveModel, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        let base = review.insert(conn).await?;
+
+        FullSubmissionReview::load(base, Vec::new(), conn).await
+    }
+
+    /// Fetches a single review, without any of its replies.
+    pub async fn fetch(review_id: i32, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        let base = submission_reviews::Entity::find_by_id(review_id)
+            .one(conn)
+            .await?;
+
+        if let Some(base) = base {
+            FullSubmissionReview::load(base, Vec::new(), conn).await
+        } else {
+            Err(AthenaError::ModelNotFound("review"))
+        }
+    }
+
+    pub async fn update(model: submission_reviews::ActiveModel, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        let base = model.update(conn).await?;
+
+        FullSubmissionReview::load(base, Vec::new(), conn).await
+    }
+
+    /// Deletes the review along with its whole reply thread. Ratings given to
+    /// them are deleted by the database.
+    pub async fn delete(self, conn: &sea_orm::DatabaseConnection) -> Result<(), AthenaError> {
+        let txn = conn.begin().await?;
+        let mut ids = vec![self.base.id];
+        let mut parents = ids.clone();
+
+        while !parents.is_empty() {
+            parents = submission_reviews::Entity::find()
+                .filter(submission_reviews::Column::ParentId.is_in(parents))
+                .all(&txn)
+                .await?
+                .into_iter()
+                .map(|reply| reply.id)
+                .collect();
+
+            ids.extend(&parents);
+        }
+
+        // Postgres checks the self-referencing key at the end of the statement,
+        // so the whole thread can go at once.
+        submission_reviews::Entity::delete_many()
+            .filter(submission_reviews::Column::Id.is_in(ids))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Finds reviews using the provided query, each with their full reply
+    /// thread attached. The query should only match top-level reviews, as
+    /// replies would otherwise show up twice.
+    pub async fn find(
+        conn: &sea_orm::DatabaseConnection,
+        fun: impl FnOnce(Select<submission_reviews::Entity>) -> Select<submission_reviews::Entity>,
+    ) -> Result<Vec<Self>, AthenaError> {
+        let base = submission_reviews::Entity::find();
+        let base = fun(base).all(conn).await?;
+
+        let mut replies: HashMap<i32, Vec<submission_reviews::Model>> = HashMap::new();
+        let mut parents: Vec<i32> = base.iter().map(|review| review.id).collect();
+
+        while !parents.is_empty() {
+            let children = submission_reviews::Entity::find()
+                .filter(submission_reviews::Column::ParentId.is_in(parents))
+                .order_by_asc(submission_reviews::Column::Id)
+                .all(conn)
+                .await?;
+
+            parents = children.iter().map(|reply| reply.id).collect();
+
+            for child in children {
+                replies.entry(child.parent_id.unwrap()).or_default().push(child);
+            }
+        }
+
+        let mut related = ThreadRelations::fetch(base.iter().chain(replies.values().flatten()), conn).await?;
+
+        base.into_iter()
+            .map(|review| FullSubmissionReview::build_thread(review, &mut replies, &mut related))
+            .collect()
+    }
+
+    pub async fn count(
+        conn: &sea_orm::DatabaseConnection,
+        fun: impl FnOnce(Select<submission_reviews::Entity>) -> Select<submission_reviews::Entity>,
+    ) -> Result<u64, AthenaError> {
+        let base = submission_reviews::Entity::find();
+
+        Ok(fun(base).count(conn).await?)
+    }
+
+    pub fn into_display(self) -> DisplaySubmissionReview {
+        let mut display = DisplaySubmissionReview::new(self.base);
+
+        display.reviewer = self.reviewer.into_display();
+        display.rating_count = self.ratings.count;
+        display.average_rating = self.ratings.average;
+        display.replies = self.replies.into_iter().map(|reply| reply.into_display()).collect();
+
+        display
+    }
+
+    fn build_thread(
+        base: submission_reviews::Model,
+        replies: &mut HashMap<i32, Vec<submission_reviews::Model>>,
+        related: &mut ThreadRelations
+    ) -> Result<Self, AthenaError> {
+        let full_replies = replies.remove(&base.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|reply| FullSubmissionReview::build_thread(reply, replies, related))
+            .collect::<Result<_, _>>()?;
+
+        related.attach(base, full_replies)
+    }
+
+    async fn load(
+        base: submission_reviews::Model,
+        replies: Vec<FullSubmissionReview>,
+        conn: &sea_orm::DatabaseConnection
+    ) -> Result<Self, AthenaError> {
+        ThreadRelations::fetch([&base], conn).await?.attach(base, replies)
+    }
+}
+
+/// The reviewers and rating summaries of every review in a thread, fetched
+/// with a query each rather than a couple per review.
+struct ThreadRelations {
+    reviewers: HashMap<i32, FullUser>,
+    ratings: HashMap<i32, RatingSummary>
+}
+
+impl ThreadRelations {
+    async fn fetch<'a>(
+        reviews: impl IntoIterator<Item = &'a submission_reviews::Model>,
+        conn: &sea_orm::DatabaseConnection
+    ) -> Result<Self, AthenaError> {
+        let (review_ids, reviewer_ids): (Vec<i32>, Vec<i32>) = reviews.into_iter()
+            .map(|review| (review.id, review.reviewer_id))
+            .unzip();
+
+        Ok(ThreadRelations {
+            reviewers: FullUser::fetch_many(&reviewer_ids, conn).await?,
+            ratings: FullRating::summarize_many(RatingType::Review, &review_ids, conn).await?
+        })
+    }
+
+    fn attach(&mut self, base: submission_reviews::Model, replies: Vec<FullSubmissionReview>) -> Result<FullSubmissionReview, AthenaError> {
+        // Reviewers usually reply more than once in a thread.
+        let reviewer = self.reviewers.get(&base.reviewer_id).cloned().ok_or(AthenaError::ModelNotFound("user"))?;
+        let ratings = self.ratings.remove(&base.id).unwrap_or_default();
+
+        Ok(FullSubmissionReview { base, reviewer, ratings, replies })
+    }
+}
\ No newline at end of file
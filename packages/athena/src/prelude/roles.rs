@@ -1,18 +1,23 @@
 use bitflags::bitflags;
-use athena_macros::generate_display;
 use sea_orm::{ActiveModelTrait, DbErr, EntityTrait};
 use crate::{entities::roles, errors::AthenaError};
 
+#[derive(Clone)]
 pub struct FullRole {
     pub base: roles::Model,
-    pub permissions: Permissions
+    pub permissions: Permissions,
+    /// The game mode the role was assigned for, when the role is tied to one.
+    pub game_mode: Option<i16>,
+    /// Whether the user only held the role in the past. Alumni roles are
+    /// shown, but don't grant any of their permissions.
+    pub alumni: bool
 }
 
 impl FullRole {
     pub async fn create(role: roles::ActiveModel, conn: &sea_orm::DatabaseConnection) -> Result<Self, DbErr> {
         let base = role.insert(conn).await?;
 
-        Ok(FullRole { base, permissions: Permissions::empty() })
+        Ok(FullRole { base, permissions: Permissions::empty(), game_mode: None, alumni: false })
     }
 
     pub async fn fetch(role_id: i32, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
@@ -23,7 +28,9 @@ impl FullRole {
         if let Some(base) = base {
             Ok(FullRole {
                 base: base.clone(),
-                permissions: Permissions::from_bits(base.permissions).unwrap_or(Permissions::empty())
+                permissions: Permissions::from_bits(base.permissions).unwrap_or(Permissions::empty()),
+                game_mode: None,
+                alumni: false
             })
         } else {
             Err(AthenaError::ModelNotFound("role"))
@@ -33,7 +40,9 @@ impl FullRole {
     pub fn from(model: roles::Model) -> Self {
         FullRole {
             base: model.clone(),
-            permissions: Permissions::from_bits(model.permissions).unwrap_or(Permissions::empty())
+            permissions: Permissions::from_bits(model.permissions).unwrap_or(Permissions::empty()),
+            game_mode: None,
+            alumni: false
         }
     }
 
@@ -42,20 +51,24 @@ impl FullRole {
 
         Ok(FullRole {
             base: base.clone(),
-            permissions: Permissions::from_bits(base.permissions).unwrap_or(Permissions::empty())
+            permissions: Permissions::from_bits(base.permissions).unwrap_or(Permissions::empty()),
+            game_mode: None,
+            alumni: false
         })
     }
 
     pub fn into_display(self) -> serde_json::Value {
         serde_json::json!({
             "id": self.base.id,
-            "permissions": self.permissions.bits()
+            "permissions": self.permissions.bits(),
+            "game_mode": self.game_mode,
+            "alumni": self.alumni
         })
     }
 }
 
 bitflags! {
-    #[derive(Default)]
+    #[derive(Default, Clone, Copy)]
     pub struct Permissions: i64 {
         // Administrator permissions
         const ADMIN                     = 1 << 0;
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use athena_macros::generate_display;
+use sea_orm::{
+    prelude::DateTime, sea_query::Expr, ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    Select, TransactionTrait
+};
+use crate::{entities::{round_game_modes, rounds, sea_orm_active_enums::RoundStatus}, errors::AthenaError};
+use super::users::FullUser;
+
+/// Every game mode a round has a captain slot for.
+const GAME_MODES: [i16; 4] = [0, 1, 2, 3];
+
+generate_display! {
+    #[display(round_game_modes::Model)]
+    DisplayRoundGameMode {
+        game_mode = i16: base.game_mode,
+        captain = Option<serde_json::Value>: None
+    }
+}
+
+generate_display! {
+    #[display(rounds::Model)]
+    DisplayRound {
+        id = i32: base.id,
+        name = String: base.name.clone(),
+        intro = String: base.intro.clone(),
+        status = RoundStatus: base.status.clone(),
+        nominations_close_at = Option<DateTime>: base.nominations_close_at,
+        voting_starts_at = Option<DateTime>: base.voting_starts_at,
+        voting_ends_at = Option<DateTime>: base.voting_ends_at,
+        created_at = DateTime: base.created_at,
+        game_modes = Vec<DisplayRoundGameMode>: Vec::new()
+    }
+}
+
+/// A game mode of a round, along with the captain picking its nominations.
+pub struct FullRoundGameMode {
+    pub base: round_game_modes::Model,
+    pub captain: Option<FullUser>
+}
+
+impl FullRoundGameMode {
+    pub fn into_display(self) -> DisplayRoundGameMode {
+        let mut display = DisplayRoundGameMode::new(self.base);
+
+        display.captain = self.captain.map(|captain| captain.into_display());
+        display
+    }
+}
+
+pub struct FullRound {
+    pub base: rounds::Model,
+    pub game_modes: Vec<FullRoundGameMode>
+}
+
+impl FullRound {
+    /// Creates a round, along with a captain slot for every game mode. Slots
+    /// are empty unless `captains` assigns them, as `(game_mode, captain_id)`.
+    pub async fn create(
+        round: rounds::ActiveModel,
+        captains: impl IntoIterator<Item = (i16, Option<i32>)>,
+        conn: &sea_orm::DatabaseConnection
+    ) -> Result<Self, AthenaError> {
+        let txn = conn.begin().await?;
+        let base = round.insert(&txn).await?;
+
+        round_game_modes::Entity::insert_many(GAME_MODES.map(|game_mode| round_game_modes::ActiveModel {
+            round_id: sea_orm::ActiveValue::Set(base.id),
+            game_mode: sea_orm::ActiveValue::Set(game_mode),
+            captain_id: sea_orm::ActiveValue::Set(None)
+        }))
+            .exec_without_returning(&txn)
+            .await?;
+
+        for (game_mode, captain_id) in captains {
+            FullRound::set_captain(base.id, game_mode, captain_id, &txn).await?;
+        }
+
+        txn.commit().await?;
+        FullRound::load(base, conn).await
+    }
+
+    pub async fn fetch(round_id: i32, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        let base = rounds::Entity::find_by_id(round_id)
+            .one(conn)
+            .await?;
+
+        if let Some(base) = base {
+            FullRound::load(base, conn).await
+        } else {
+            Err(AthenaError::ModelNotFound("round"))
+        }
+    }
+
+    /// Updates the round along with the captains of the game modes in
+    /// `captains`, as `(game_mode, captain_id)`.
+    pub async fn update(
+        model: rounds::ActiveModel,
+        captains: impl IntoIterator<Item = (i16, Option<i32>)>,
+        conn: &sea_orm::DatabaseConnection
+    ) -> Result<Self, AthenaError> {
+        let txn = conn.begin().await?;
+        let base = model.update(&txn).await?;
+
+        for (game_mode, captain_id) in captains {
+            FullRound::set_captain(base.id, game_mode, captain_id, &txn).await?;
+        }
+
+        txn.commit().await?;
+        FullRound::load(base, conn).await
+    }
+
+    pub async fn find(
+        conn: &sea_orm::DatabaseConnection,
+        fun: impl FnOnce(Select<rounds::Entity>) -> Select<rounds::Entity>,
+    ) -> Result<Vec<Self>, AthenaError> {
+        let base = rounds::Entity::find();
+        let base = fun(base).all(conn).await?;
+
+        FullRound::load_many(base, conn).await
+    }
+
+    pub async fn count(
+        conn: &sea_orm::DatabaseConnection,
+        fun: impl FnOnce(Select<rounds::Entity>) -> Select<rounds::Entity>,
+    ) -> Result<u64, AthenaError> {
+        let base = rounds::Entity::find();
+
+        Ok(fun(base).count(conn).await?)
+    }
+
+    /// Assigns the captain of one of the round's game modes, or empties the
+    /// slot when `captain_id` is `None`.
+    pub async fn set_captain(
+        round_id: i32,
+        game_mode: i16,
+        captain_id: Option<i32>,
+        conn: &impl ConnectionTrait
+    ) -> Result<(), AthenaError> {
+        let result = round_game_modes::Entity::update_many()
+            .col_expr(round_game_modes::Column::CaptainId, Expr::value(captain_id))
+            .filter(round_game_modes::Column::RoundId.eq(round_id))
+            .filter(round_game_modes::Column::GameMode.eq(game_mode))
+            .exec(conn)
+            .await?;
+
+        if result.rows_affected == 0 {
+            return Err(AthenaError::ModelNotFound("round game mode"));
+        }
+
+        Ok(())
+    }
+
+    pub fn into_display(self) -> DisplayRound {
+        let mut display = DisplayRound::new(self.base);
+
+        display.game_modes = self.game_modes
+            .into_iter()
+            .map(|game_mode| game_mode.into_display())
+            .collect();
+
+        display
+    }
+
+    async fn load(base: rounds::Model, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        Ok(FullRound::load_many(vec![base], conn).await?.remove(0))
+    }
+
+    /// Loads the captain slots of every round, and the captains in them, with
+    /// a query each rather than a few per round.
+    async fn load_many(base: Vec<rounds::Model>, conn: &sea_orm::DatabaseConnection) -> Result<Vec<Self>, AthenaError> {
+        let round_ids: Vec<i32> = base.iter().map(|round| round.id).collect();
+        let slots = round_game_modes::Entity::find()
+            .filter(round_game_modes::Column::RoundId.is_in(round_ids))
+            .order_by_asc(round_game_modes::Column::GameMode)
+            .all(conn)
+            .await?;
+
+        let captain_ids: Vec<i32> = slots.iter().filter_map(|slot| slot.captain_id).collect();
+        let captains = FullUser::fetch_many(&captain_ids, conn).await?;
+        let mut game_modes: HashMap<i32, Vec<FullRoundGameMode>> = HashMap::new();
+
+        for slot in slots {
+            // Users can captain several game modes, hence the clones.
+            let captain = match slot.captain_id {
+                Some(captain_id) => Some(captains.get(&captain_id).cloned().ok_or(AthenaError::ModelNotFound("user"))?),
+                None => None
+            };
+
+            game_modes.entry(slot.round_id).or_default().push(FullRoundGameMode { base: slot, captain });
+        }
+
+        Ok(base.into_iter().map(|base| FullRound {
+            game_modes: game_modes.remove(&base.id).unwrap_or_default(),
+            base
+        }).collect())
+    }
+}
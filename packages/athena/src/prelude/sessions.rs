@@ -0,0 +1,88 @@
+use chrono::{Duration, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter};
+use crate::{entities::sessions, errors::AthenaError, security::hash_token};
+
+/// How long a session stays valid without any activity.
+pub const SESSION_LIFETIME: Duration = Duration::days(30);
+
+/// How often an active session gets its expiry pushed back, so that not every
+/// request ends up writing to the database.
+const SESSION_RENEWAL_INTERVAL: Duration = Duration::hours(1);
+
+/// A session, which only ever stores the hash of its token. Every method
+/// taking a token hashes it before it reaches the database.
+pub struct FullSession {
+    pub base: sessions::Model
+}
+
+impl FullSession {
+    pub async fn create(user_id: i32, token: &str, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        let base = sessions::ActiveModel {
+            id: sea_orm::ActiveValue::NotSet,
+            user_id: sea_orm::ActiveValue::Set(user_id),
+            session_token: sea_orm::ActiveValue::Set(hash_token(token)),
+            expires_at: sea_orm::ActiveValue::Set(Utc::now().naive_utc() + SESSION_LIFETIME)
+        }.insert(conn).await?;
+
+        Ok(FullSession { base })
+    }
+
+    /// Fetches the session behind a token, as long as it hasn't expired yet.
+    pub async fn fetch(token: &str, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        let base = sessions::Entity::find()
+            .filter(sessions::Column::SessionToken.eq(hash_token(token)))
+            .filter(sessions::Column::ExpiresAt.gt(Utc::now().naive_utc()))
+            .one(conn)
+            .await?;
+
+        if let Some(base) = base {
+            Ok(FullSession { base })
+        } else {
+            Err(AthenaError::ModelNotFound("session"))
+        }
+    }
+
+    /// Pushes the expiry of the session back, keeping sessions that are in
+    /// use alive. Also tells whether the expiry moved, as the cookie carrying
+    /// the session has to follow it.
+    pub async fn renew(self, conn: &sea_orm::DatabaseConnection) -> Result<(Self, bool), AthenaError> {
+        let expires_at = Utc::now().naive_utc() + SESSION_LIFETIME;
+
+        if expires_at - self.base.expires_at < SESSION_RENEWAL_INTERVAL {
+            return Ok((self, false));
+        }
+
+        let mut model = self.base.into_active_model();
+        model.expires_at = sea_orm::ActiveValue::Set(expires_at);
+
+        Ok((FullSession { base: model.update(conn).await? }, true))
+    }
+
+    pub async fn delete(token: &str, conn: &sea_orm::DatabaseConnection) -> Result<(), AthenaError> {
+        sessions::Entity::delete_many()
+            .filter(sessions::Column::SessionToken.eq(hash_token(token)))
+            .exec(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_all(user_id: i32, conn: &sea_orm::DatabaseConnection) -> Result<u64, AthenaError> {
+        let result = sessions::Entity::delete_many()
+            .filter(sessions::Column::UserId.eq(user_id))
+            .exec(conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    /// Removes every session that has already expired.
+    pub async fn purge_expired(conn: &sea_orm::DatabaseConnection) -> Result<u64, AthenaError> {
+        let result = sessions::Entity::delete_many()
+            .filter(sessions::Column::ExpiresAt.lte(Utc::now().naive_utc()))
+            .exec(conn)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
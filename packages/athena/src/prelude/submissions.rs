@@ -1,18 +1,39 @@
+use std::collections::HashMap;
+
 use athena_macros::generate_display;
-use sea_orm::{ActiveModelTrait, DbErr, EntityTrait, Select};
-use crate::{entities::submissions, errors::AthenaError};
-use super::{users::{FullUser}, AsyncFromDatabase};
+use sea_orm::{prelude::DateTime, ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QuerySelect, Select};
+use crate::{entities::{sea_orm_active_enums::RatingType, submission_reviews, submissions}, errors::AthenaError};
+use super::{beatmaps::{DisplayBeatmap, DisplayBeatmapset, FullBeatmapset}, ratings::{FullRating, RatingSummary}, users::FullUser, AsyncFromDatabase};
+
+generate_display! {
+    #[display(submissions::Model)]
+    DisplaySubmission {
+        id = i32: base.id,
+        game_mode = i16: base.game_mode,
+        submitted_at = DateTime: base.submitted_at,
+        submitter = serde_json::Value: serde_json::Value::Null,
+        beatmapset = Option<DisplayBeatmapset>: None,
+        beatmaps = Vec<DisplayBeatmap>: Vec::new(),
+        review_count = u64: 0,
+        rating_count = u64: 0,
+        rating_sum = i64: 0,
+        average_rating = Option<f64>: None
+    }
+}
 
 pub struct FullSubmission {
     pub base: submissions::Model,
-    pub submitter: FullUser
+    pub submitter: FullUser,
+    pub beatmapset: FullBeatmapset,
+    pub review_count: u64,
+    pub ratings: RatingSummary
 }
 
 impl FullSubmission {
     pub async fn create(submission: submissions::ActiveModel, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
         let base = submission.insert(conn).await?;
-        let submitter = FullUser::fetch(base.submitter_id, conn).await?;
-        Ok(FullSubmission { base, submitter })
+
+        FullSubmission::load(base, conn).await
     }
 
     pub async fn fetch(submission_id: i32, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
@@ -21,9 +42,7 @@ impl FullSubmission {
             .await?;
 
         if let Some(base) = base {
-            let submitter = FullUser::fetch(base.submitter_id, conn).await?;
-            
-            Ok(FullSubmission { base, submitter })
+            FullSubmission::load(base, conn).await
         } else {
             Err(AthenaError::ModelNotFound("submission"))
         }
@@ -35,25 +54,86 @@ impl FullSubmission {
     ) -> Result<Vec<Self>, AthenaError> {
         let base = submissions::Entity::find();
         let base = fun(base);
-    
+
         let base = base.all(conn).await?;
-        let mut full_submissions = Vec::new();
-    
-        for submission in base {
-            full_submissions.push(FullSubmission::from_async(submission, conn).await);
-        }
-    
-        Ok(full_submissions)
+
+        FullSubmission::load_many(base, conn).await
+    }
+
+    pub async fn count(
+        conn: &sea_orm::DatabaseConnection,
+        fun: impl FnOnce(Select<submissions::Entity>) -> Select<submissions::Entity>,
+    ) -> Result<u64, AthenaError> {
+        let base = submissions::Entity::find();
+
+        Ok(fun(base).count(conn).await?)
+    }
+
+    pub fn into_display(self) -> DisplaySubmission {
+        let mut display = DisplaySubmission::new(self.base);
+
+        display.submitter = self.submitter.into_display();
+        display.beatmaps = self.beatmapset.beatmaps
+            .into_iter()
+            .filter(|beatmap| beatmap.base.game_mode == display.game_mode)
+            .map(|beatmap| DisplayBeatmap::new(beatmap.base))
+            .collect();
+        display.beatmapset = Some(DisplayBeatmapset::new(self.beatmapset.base));
+        display.review_count = self.review_count;
+        display.rating_count = self.ratings.count;
+        display.rating_sum = self.ratings.sum;
+        display.average_rating = self.ratings.average;
+
+        display
+    }
+
+    async fn load(base: submissions::Model, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        Ok(FullSubmission::load_many(vec![base], conn).await?.remove(0))
+    }
+
+    /// Loads what every submission points at with a query per table, rather
+    /// than a few per submission.
+    async fn load_many(base: Vec<submissions::Model>, conn: &sea_orm::DatabaseConnection) -> Result<Vec<Self>, AthenaError> {
+        let submission_ids: Vec<i32> = base.iter().map(|submission| submission.id).collect();
+        let submitter_ids: Vec<i32> = base.iter().map(|submission| submission.submitter_id).collect();
+        let beatmapset_ids: Vec<i32> = base.iter().map(|submission| submission.beatmapset_id).collect();
+
+        let submitters = FullUser::fetch_many(&submitter_ids, conn).await?;
+        let beatmapsets = FullBeatmapset::fetch_many(&beatmapset_ids, conn).await?;
+        let mut ratings = FullRating::summarize_many(RatingType::Submission, &submission_ids, conn).await?;
+
+        // Replies are part of a review's thread, so only top-level reviews count.
+        let review_counts: HashMap<i32, i64> = submission_reviews::Entity::find()
+            .select_only()
+            .column(submission_reviews::Column::SubmissionId)
+            .column_as(submission_reviews::Column::Id.count(), "count")
+            .filter(submission_reviews::Column::SubmissionId.is_in(submission_ids))
+            .filter(submission_reviews::Column::ParentId.is_null())
+            .group_by(submission_reviews::Column::SubmissionId)
+            .into_tuple::<(i32, i64)>()
+            .all(conn)
+            .await?
+            .into_iter()
+            .collect();
+
+        // Submitters and sets are shared between submissions, hence the clones.
+        base.into_iter().map(|base| {
+            let submitter = submitters.get(&base.submitter_id).cloned().ok_or(AthenaError::ModelNotFound("user"))?;
+            let beatmapset = beatmapsets.get(&base.beatmapset_id).cloned().ok_or(AthenaError::ModelNotFound("beatmapset"))?;
+
+            Ok(FullSubmission {
+                review_count: review_counts.get(&base.id).copied().unwrap_or(0) as u64,
+                ratings: ratings.remove(&base.id).unwrap_or_default(),
+                base,
+                submitter,
+                beatmapset
+            })
+        }).collect()
     }
 }
 
 impl AsyncFromDatabase<submissions::Model> for FullSubmission {
-    async fn from_async(value: submissions::Model, conn: &sea_orm::DatabaseConnection) -> Self {
-        FullSubmission {
-            base: value.clone(),
-            submitter: FullUser::fetch(value.submitter_id, conn)
-            .await
-            .unwrap()
-        }
+    async fn from_async(value: submissions::Model, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        FullSubmission::load(value, conn).await
     }
 }
\ No newline at end of file
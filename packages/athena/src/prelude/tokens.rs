@@ -0,0 +1,89 @@
+use chrono::{DateTime, Duration, Utc};
+use rosu_v2::prelude::Token;
+use serde::{Deserialize, Serialize};
+use crate::security;
+
+/// How long before their expiry tokens are refreshed, so that they don't run
+/// out in the middle of a request.
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::minutes(5);
+
+/// The osu! OAuth tokens of a user, which are only ever stored encrypted in
+/// `users.tokens`.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct OsuTokens {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: DateTime<Utc>
+}
+
+impl OsuTokens {
+    pub fn from_token(token: &Token) -> Self {
+        OsuTokens {
+            access_token: token.access_token.clone(),
+            refresh_token: token.refresh_token.clone(),
+            expires_at: Utc::now() + Duration::seconds(token.expires_in)
+        }
+    }
+
+    /// Builds a token for the osu! client. An access token that is about to
+    /// expire is reported as already expired, making the client refresh it.
+    pub fn into_token(self) -> Token {
+        let expires_in = (self.expires_at - TOKEN_EXPIRY_MARGIN - Utc::now()).num_seconds().max(0);
+
+        Token {
+            access_token: self.access_token,
+            refresh_token: self.refresh_token,
+            expires_in
+        }
+    }
+
+    /// Encrypts the tokens for the given user, producing the value stored in
+    /// `users.tokens`.
+    pub fn encrypt(&self, user_id: i32) -> serde_json::Value {
+        let plaintext = serde_json::to_vec(self).unwrap();
+
+        serde_json::json!({
+            "ciphertext": security::encrypt(&plaintext, &user_id.to_be_bytes())
+        })
+    }
+
+    /// Decrypts the tokens stored for the given user. Users that never logged
+    /// in, or whose tokens can't be decrypted, have none.
+    pub fn decrypt(value: &serde_json::Value, user_id: i32) -> Option<Self> {
+        let ciphertext = value.get("ciphertext")?.as_str()?;
+        let plaintext = security::decrypt(ciphertext, &user_id.to_be_bytes())?;
+
+        serde_json::from_slice(&plaintext).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(expires_in: Duration) -> OsuTokens {
+        OsuTokens {
+            access_token: "access".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            expires_at: Utc::now() + expires_in
+        }
+    }
+
+    #[test]
+    fn tokens_survive_encryption_for_their_user_only() {
+        security::init_for_tests();
+
+        let tokens = tokens(Duration::hours(1));
+        let encrypted = tokens.encrypt(1);
+
+        assert!(OsuTokens::decrypt(&encrypted, 1) == Some(tokens));
+        assert!(OsuTokens::decrypt(&encrypted, 2).is_none());
+        assert!(OsuTokens::decrypt(&serde_json::json!({}), 1).is_none());
+    }
+
+    #[test]
+    fn tokens_about_to_expire_are_reported_as_expired() {
+        assert_eq!(tokens(Duration::minutes(2)).into_token().expires_in, 0);
+        assert!(tokens(Duration::hours(1)).into_token().expires_in > 50 * 60);
+    }
+}
@@ -1,17 +1,39 @@
-use sea_orm::{ActiveModelTrait, ColumnTrait, DbErr, EntityTrait, QueryFilter, TryIntoModel};
-use crate::{entities::{self, role_assignments, roles, sessions, users}, errors::AthenaError};
-use super::roles::{FullRole};
+use std::{collections::HashMap, future::Future, sync::Arc};
 
+use rosu_v2::{error::OsuError, prelude::UserExtended, Osu};
+use sea_orm::{
+    sea_query::{Expr, OnConflict}, ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, IntoActiveModel, PaginatorTrait,
+    QueryFilter, QueryOrder, Select, TransactionTrait
+};
+use crate::{entities::{role_assignments, roles, user_names, users}, environment::LovedEnvironment, errors::AthenaError};
+use super::{roles::{FullRole, Permissions}, sessions::FullSession, tokens::OsuTokens};
+
+#[derive(Clone)]
 pub struct FullUser {
     pub base: users::Model,
-    pub roles: Vec<FullRole>
+    pub roles: Vec<FullRole>,
+    pub previous_usernames: Vec<String>
 }
 
 impl FullUser {
     pub async fn create(user: users::ActiveModel, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
         let base = user.insert(conn).await?;
 
-        Ok(FullUser { base, roles: Vec::new() })
+        Ok(FullUser { base, roles: Vec::new(), previous_usernames: Vec::new() })
+    }
+
+    /// Inserts every user that isn't stored yet, leaving existing rows untouched.
+    pub async fn create_missing(users: Vec<users::ActiveModel>, conn: &impl ConnectionTrait) -> Result<(), AthenaError> {
+        if users.is_empty() {
+            return Ok(());
+        }
+
+        users::Entity::insert_many(users)
+            .on_conflict(OnConflict::column(users::Column::Id).do_nothing().to_owned())
+            .exec_without_returning(conn)
+            .await?;
+
+        Ok(())
     }
 
     pub async fn fetch(user_id: i32, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
@@ -20,10 +42,7 @@ impl FullUser {
             .await?;
 
         if let Some(base) = base {
-            Ok(FullUser {
-                base: base.clone(),
-                roles: get_user_roles(base.id, conn).await.unwrap_or(Vec::new())
-            })
+            FullUser::load(base, conn).await
         } else {
             Err(AthenaError::ModelNotFound("user"))
         }
@@ -32,38 +51,190 @@ impl FullUser {
     pub async fn update(model: users::ActiveModel, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
         let base = model.update(conn).await?;
 
-        Ok(FullUser {
-            base: base.clone(),
-            roles: get_user_roles(base.id, conn).await.unwrap_or(Vec::new())
-        })
+        FullUser::load(base, conn).await
     }
 
     pub fn from(model: users::Model, roles: Vec<FullRole>) -> Self {
-        FullUser { base: model.clone(), roles }
+        FullUser { base: model.clone(), roles, previous_usernames: Vec::new() }
+    }
+
+    pub async fn find(
+        conn: &sea_orm::DatabaseConnection,
+        fun: impl FnOnce(Select<users::Entity>) -> Select<users::Entity>,
+    ) -> Result<Vec<Self>, AthenaError> {
+        let base = users::Entity::find();
+        let base = fun(base).all(conn).await?;
+
+        FullUser::load_many(base, conn).await
     }
 
-    pub async fn from_session(token: &str, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
-        let session = sessions::Entity::find()
-            .filter(sessions::Column::SessionToken.eq(token))
+    /// Fetches several users at once, keyed by their identifier. Users that
+    /// aren't stored are left out.
+    pub async fn fetch_many(user_ids: &[i32], conn: &sea_orm::DatabaseConnection) -> Result<HashMap<i32, Self>, AthenaError> {
+        let base = users::Entity::find()
+            .filter(users::Column::Id.is_in(user_ids.iter().copied()))
+            .all(conn)
+            .await?;
+
+        Ok(FullUser::load_many(base, conn)
+            .await?
+            .into_iter()
+            .map(|user| (user.base.id, user))
+            .collect())
+    }
+
+    pub async fn count(
+        conn: &sea_orm::DatabaseConnection,
+        fun: impl FnOnce(Select<users::Entity>) -> Select<users::Entity>,
+    ) -> Result<u64, AthenaError> {
+        let base = users::Entity::find();
+
+        Ok(fun(base).count(conn).await?)
+    }
+
+    /// Creates or updates the user from their osu! profile, syncing their
+    /// username, country and restricted status. Renames are kept track of.
+    pub async fn upsert_from_osu(user: &UserExtended, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        let user_id: i32 = user.user_id.try_into().unwrap();
+        let username = user.username.to_string();
+        let country = Some(user.country_code.to_string());
+        let restricted = user.is_restricted.unwrap_or(false);
+        let fetched_at = chrono::Utc::now().naive_utc();
+
+        let existing = users::Entity::find_by_id(user_id)
             .one(conn)
             .await?;
 
-        if let Some(session) = session {
-            let user = users::Entity::find_by_id(session.user_id)
-                .one(conn)
-                .await?;
-
-            if let Some(user) = user {
-                Ok(FullUser {
-                    base: user.clone(),
-                    roles: get_user_roles(user.id, conn).await.unwrap_or(Vec::new())
-                })
-            } else {
-                Err(AthenaError::ModelNotFound("user"))
+        if let Some(existing) = existing {
+            // The rename is only recorded if the user's row goes along with it.
+            let txn = conn.begin().await?;
+
+            if let Some(previous) = existing.username.as_deref().filter(|previous| *previous != username) {
+                FullUser::record_username(existing.id, previous, &txn).await?;
             }
+
+            let mut model = existing.into_active_model();
+            model.username = sea_orm::ActiveValue::Set(Some(username));
+            model.country = sea_orm::ActiveValue::Set(country);
+            model.restricted = sea_orm::ActiveValue::Set(restricted);
+            model.api_fetched_at = sea_orm::ActiveValue::Set(fetched_at);
+
+            let base = model.update(&txn).await?;
+            txn.commit().await?;
+
+            FullUser::load(base, conn).await
         } else {
-            Err(AthenaError::ModelNotFound("session"))
+            FullUser::create(users::ActiveModel {
+                id: sea_orm::ActiveValue::Set(user_id),
+                username: sea_orm::ActiveValue::Set(Some(username)),
+                country: sea_orm::ActiveValue::Set(country),
+                restricted: sea_orm::ActiveValue::Set(restricted),
+                api_fetched_at: sea_orm::ActiveValue::Set(fetched_at),
+                tokens: sea_orm::ActiveValue::Set(serde_json::json!({}))
+            }, conn).await
+        }
+    }
+
+    /// Marks a user the osu! API no longer returns as restricted. Users that
+    /// aren't stored are left alone, which is why nothing is returned.
+    pub async fn mark_unavailable(user_id: i32, conn: &sea_orm::DatabaseConnection) -> Result<(), AthenaError> {
+        users::Entity::update_many()
+            .col_expr(users::Column::Restricted, Expr::value(true))
+            .col_expr(users::Column::ApiFetchedAt, Expr::value(chrono::Utc::now().naive_utc()))
+            .filter(users::Column::Id.eq(user_id))
+            .exec(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Stores the user's osu! tokens, encrypted.
+    pub async fn store_tokens(&mut self, tokens: &OsuTokens, conn: &sea_orm::DatabaseConnection) -> Result<(), AthenaError> {
+        let mut model = self.base.clone().into_active_model();
+        model.tokens = sea_orm::ActiveValue::Set(tokens.encrypt(self.base.id));
+
+        self.base = model.update(conn).await?;
+        Ok(())
+    }
+
+    /// Keeps track of a username the user no longer goes by. Empty usernames
+    /// aren't worth keeping, so they're skipped.
+    pub async fn record_username(user_id: i32, username: &str, conn: &impl ConnectionTrait) -> Result<(), AthenaError> {
+        if username.is_empty() {
+            return Ok(());
         }
+
+        user_names::ActiveModel {
+            id: sea_orm::ActiveValue::NotSet,
+            user_id: sea_orm::ActiveValue::Set(user_id),
+            username: sea_orm::ActiveValue::Set(username.to_string()),
+            changed_at: sea_orm::ActiveValue::Set(chrono::Utc::now().naive_utc())
+        }.insert(conn).await?;
+
+        Ok(())
+    }
+
+    /// Fetches the user behind a session token, renewing the session, along
+    /// with whether the session got renewed. Expired sessions are treated as
+    /// if they didn't exist.
+    pub async fn from_session(token: &str, conn: &sea_orm::DatabaseConnection) -> Result<(Self, bool), AthenaError> {
+        let (session, renewed) = FullSession::fetch(token, conn)
+            .await?
+            .renew(conn)
+            .await?;
+
+        Ok((FullUser::fetch(session.base.user_id, conn).await?, renewed))
+    }
+
+    /// Runs `fun` with an osu! client acting on behalf of the user. The client
+    /// refreshes expired tokens by itself and osu! hands out a new refresh
+    /// token each time, so the tokens are persisted again once `fun` is done
+    /// whenever they changed, even if it failed.
+    pub async fn with_osu_client<T, Fut>(
+        &mut self,
+        env: &LovedEnvironment,
+        conn: &sea_orm::DatabaseConnection,
+        fun: impl FnOnce(Arc<Osu>) -> Fut
+    ) -> Result<T, AthenaError>
+        where Fut: Future<Output = Result<T, OsuError>>
+    {
+        let tokens = OsuTokens::decrypt(&self.base.tokens, self.base.id).ok_or(AthenaError::MissingTokens)?;
+        let osu = Arc::new(
+            Osu::builder()
+                .url(env.osu_url.clone())
+                .client_id(env.osu_client_id)
+                .client_secret(env.osu_client_secret.clone())
+                .with_token(tokens.clone().into_token())
+                .build()
+                .await?
+        );
+
+        let result = fun(osu.clone()).await;
+        let refreshed = OsuTokens::from_token(&osu.token());
+
+        if refreshed.access_token != tokens.access_token || refreshed.refresh_token != tokens.refresh_token {
+            self.store_tokens(&refreshed, conn).await?;
+        }
+
+        Ok(result?)
+    }
+
+    /// Whether any of the user's current roles grants the permission.
+    /// Administrators are granted every permission.
+    pub fn has_permission(&self, permission: Permissions) -> bool {
+        let granted = (Permissions::ADMIN | permission).bits();
+
+        self.roles.iter().any(|role| !role.alumni && role.permissions.bits() & granted != 0)
+    }
+
+    /// Whether any of the user's current roles grants the permission for the
+    /// game mode. Roles that aren't tied to a game mode grant it for every mode.
+    pub fn has_game_mode_permission(&self, permission: Permissions, game_mode: i16) -> bool {
+        let granted = (Permissions::ADMIN | permission).bits();
+
+        self.roles.iter().any(|role| {
+            !role.alumni && role.permissions.bits() & granted != 0 && role.game_mode.is_none_or(|mode| mode == game_mode)
+        })
     }
 
     pub fn into_display(self) -> serde_json::Value {
@@ -72,21 +243,136 @@ impl FullUser {
             "username": self.base.username,
             "country": self.base.country,
             "restricted": self.base.restricted,
+            "previous_usernames": self.previous_usernames,
             "roles": self.roles.into_iter().map(|r| r.into_display()).collect::<Vec<_>>()
         })
     }
+
+    async fn load(base: users::Model, conn: &sea_orm::DatabaseConnection) -> Result<Self, AthenaError> {
+        Ok(FullUser::load_many(vec![base], conn).await?.remove(0))
+    }
+
+    /// Loads the roles and previous usernames of every user in a couple of
+    /// queries, rather than a few per user.
+    async fn load_many(base: Vec<users::Model>, conn: &sea_orm::DatabaseConnection) -> Result<Vec<Self>, AthenaError> {
+        let user_ids: Vec<i32> = base.iter().map(|user| user.id).collect();
+        let mut roles = get_user_roles(&user_ids, conn).await?;
+        let mut previous_usernames: HashMap<i32, Vec<String>> = HashMap::new();
+
+        let names = user_names::Entity::find()
+            .filter(user_names::Column::UserId.is_in(user_ids))
+            .order_by_desc(user_names::Column::ChangedAt)
+            .all(conn)
+            .await?;
+
+        for name in names {
+            previous_usernames.entry(name.user_id).or_default().push(name.username);
+        }
+
+        Ok(base.into_iter().map(|base| FullUser {
+            roles: roles.remove(&base.id).unwrap_or_default(),
+            previous_usernames: previous_usernames.remove(&base.id).unwrap_or_default(),
+            base
+        }).collect())
+    }
 }
 
-async fn get_user_roles(user_id: i32, conn: &sea_orm::DatabaseConnection) -> Result<Vec<FullRole>, DbErr> {
+/// Fetches the roles of every user, keyed by their identifier.
+async fn get_user_roles(user_ids: &[i32], conn: &sea_orm::DatabaseConnection) -> Result<HashMap<i32, Vec<FullRole>>, DbErr> {
     let role_tuple = role_assignments::Entity::find()
-        .filter(role_assignments::Column::UserId.eq(user_id))
+        .filter(role_assignments::Column::UserId.is_in(user_ids.iter().copied()))
         .find_also_related(roles::Entity)
         .all(conn)
         .await?;
 
-    if role_tuple.is_empty() {
-        Ok(Vec::new())
-    } else {
-        Ok(role_tuple.into_iter().map(|(_, role)| FullRole::from(role.unwrap())).collect())
+    let mut user_roles: HashMap<i32, Vec<FullRole>> = HashMap::new();
+
+    for (assignment, role) in role_tuple {
+        let Some(role) = role else {
+            continue;
+        };
+
+        let mut role = FullRole::from(role);
+        role.alumni = assignment.alumni;
+
+        if role.base.has_gamemode {
+            role.game_mode = Some(assignment.game_mode);
+        }
+
+        user_roles.entry(assignment.user_id).or_default().push(role);
     }
-}
\ No newline at end of file
+
+    Ok(user_roles)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn role(permissions: Permissions, game_mode: Option<i16>, alumni: bool) -> FullRole {
+        FullRole {
+            base: roles::Model {
+                id: 1,
+                name: "Role".to_string(),
+                r#type: 0,
+                permissions: permissions.bits(),
+                has_gamemode: game_mode.is_some()
+            },
+            permissions,
+            game_mode,
+            alumni
+        }
+    }
+
+    fn user_with(roles: Vec<FullRole>) -> FullUser {
+        FullUser {
+            base: users::Model {
+                id: 1,
+                username: Some("User".to_string()),
+                country: None,
+                restricted: false,
+                api_fetched_at: chrono::DateTime::UNIX_EPOCH.naive_utc(),
+                tokens: serde_json::Value::Null
+            },
+            roles,
+            previous_usernames: Vec::new()
+        }
+    }
+
+    #[test]
+    fn any_of_the_requested_permissions_is_enough() {
+        let user = user_with(vec![role(Permissions::MANAGE_PICKS, None, false)]);
+
+        assert!(user.has_permission(Permissions::MANAGE_PICKS));
+        assert!(user.has_permission(Permissions::MANAGE_PICKS | Permissions::MANAGE_ROUNDS));
+        assert!(!user.has_permission(Permissions::MANAGE_ROUNDS));
+        assert!(!user_with(Vec::new()).has_permission(Permissions::VIEW_ROUNDS));
+    }
+
+    #[test]
+    fn admins_have_every_permission() {
+        let user = user_with(vec![role(Permissions::ADMIN, None, false)]);
+
+        assert!(user.has_permission(Permissions::MANAGE_ROLES));
+        assert!(user.has_game_mode_permission(Permissions::MANAGE_GAMEMODE_PICKS, 3));
+    }
+
+    #[test]
+    fn alumni_roles_grant_nothing() {
+        let user = user_with(vec![role(Permissions::ADMIN | Permissions::VIEW_ROUNDS, None, true)]);
+
+        assert!(!user.has_permission(Permissions::VIEW_ROUNDS));
+        assert!(!user.has_game_mode_permission(Permissions::VIEW_ROUNDS, 0));
+    }
+
+    #[test]
+    fn game_mode_permissions_are_scoped_to_the_role_game_mode() {
+        let user = user_with(vec![
+            role(Permissions::MANAGE_GAMEMODE_PICKS, Some(1), false),
+            role(Permissions::MANAGE_METADATA, None, false)
+        ]);
+
+        assert!(user.has_game_mode_permission(Permissions::MANAGE_GAMEMODE_PICKS, 1));
+        assert!(!user.has_game_mode_permission(Permissions::MANAGE_GAMEMODE_PICKS, 2));
+        assert!(user.has_game_mode_permission(Permissions::MANAGE_METADATA, 2));
+    }
+}
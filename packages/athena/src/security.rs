@@ -0,0 +1,159 @@
+use std::sync::OnceLock;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::{aead, hkdf, hmac, rand::{SecureRandom, SystemRandom}};
+
+static SERVER_SECRET: OnceLock<String> = OnceLock::new();
+static TOKEN_KEY: OnceLock<hmac::Key> = OnceLock::new();
+static ENCRYPTION_KEY: OnceLock<aead::LessSafeKey> = OnceLock::new();
+
+/// Sets the secret every key is derived from. Has to be called on startup,
+/// before any token gets hashed or any data encrypted.
+pub fn init(server_secret: &str) {
+    let _ = SERVER_SECRET.set(server_secret.to_string());
+}
+
+/// Generates a random token with 256 bits of entropy, encoded so that it can
+/// be safely used in URLs, headers and cookies.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("Failed to generate random bytes");
+
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Hashes a token with `SERVER_SECRET` as the key. Only the hash is meant to
+/// be stored, so a leaked database doesn't leak usable tokens.
+pub fn hash_token(token: &str) -> String {
+    let key = TOKEN_KEY.get_or_init(|| hmac::Key::new(hmac::HMAC_SHA256, server_secret()));
+
+    URL_SAFE_NO_PAD.encode(hmac::sign(key, token.as_bytes()))
+}
+
+/// Encrypts data with a key derived from `SERVER_SECRET`. The associated data
+/// isn't stored, but the same has to be passed to [`decrypt`], which ties the
+/// ciphertext to whatever it belongs to.
+pub fn encrypt(plaintext: &[u8], associated_data: &[u8]) -> String {
+    let mut nonce = [0u8; aead::NONCE_LEN];
+
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .expect("Failed to generate random bytes");
+
+    let mut in_out = plaintext.to_vec();
+
+    encryption_key()
+        .seal_in_place_append_tag(aead::Nonce::assume_unique_for_key(nonce), aead::Aad::from(associated_data), &mut in_out)
+        .expect("Failed to encrypt data");
+
+    URL_SAFE_NO_PAD.encode([nonce.as_slice(), &in_out].concat())
+}
+
+/// Decrypts data produced by [`encrypt`], failing if it was tampered with or
+/// belongs to something else.
+pub fn decrypt(ciphertext: &str, associated_data: &[u8]) -> Option<Vec<u8>> {
+    let bytes = URL_SAFE_NO_PAD.decode(ciphertext).ok()?;
+
+    if bytes.len() < aead::NONCE_LEN {
+        return None;
+    }
+
+    let (nonce, sealed) = bytes.split_at(aead::NONCE_LEN);
+    let nonce = aead::Nonce::try_assume_unique_for_key(nonce).ok()?;
+    let mut in_out = sealed.to_vec();
+    let plaintext = encryption_key()
+        .open_in_place(nonce, aead::Aad::from(associated_data), &mut in_out)
+        .ok()?;
+
+    Some(plaintext.to_vec())
+}
+
+fn encryption_key() -> &'static aead::LessSafeKey {
+    ENCRYPTION_KEY.get_or_init(|| {
+        // Derived separately, so the key never matches the one used for hashing.
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, b"loved").extract(server_secret());
+        let okm = prk
+            .expand(&[b"encryption"], &aead::CHACHA20_POLY1305)
+            .expect("Failed to derive encryption key");
+
+        aead::LessSafeKey::new(aead::UnboundKey::from(okm))
+    })
+}
+
+fn server_secret() -> &'static [u8] {
+    SERVER_SECRET.get().expect("security::init must be called on startup").as_bytes()
+}
+
+
+/// Initializes the secret with the same value for every test, as it can only
+/// be set once per process.
+#[cfg(test)]
+pub(crate) fn init_for_tests() {
+    init("a server secret used only by the tests");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_tokens_are_random_and_url_safe() {
+        let token = generate_token();
+
+        assert_eq!(URL_SAFE_NO_PAD.decode(&token).map(|bytes| bytes.len()), Ok(32));
+        assert_ne!(token, generate_token());
+    }
+
+    #[test]
+    fn tokens_are_hashed_with_hmac_sha256() {
+        init_for_tests();
+
+        assert_eq!(hash_token("session token"), "QI58qkjl9lBk5kKsmSc5v1uR6ctHU8tq2jPe1UDDffk");
+    }
+
+    #[test]
+    fn hashes_tell_tokens_apart() {
+        init_for_tests();
+
+        assert_eq!(hash_token("session token"), hash_token("session token"));
+        assert_ne!(hash_token("session token"), hash_token("session token "));
+    }
+    #[test]
+    fn encrypted_data_decrypts_with_the_same_associated_data() {
+        init_for_tests();
+
+        let ciphertext = encrypt(b"osu! tokens", b"user 1");
+
+        assert_eq!(decrypt(&ciphertext, b"user 1").as_deref(), Some(b"osu! tokens".as_slice()));
+    }
+
+    #[test]
+    fn encryption_uses_a_fresh_nonce_every_time() {
+        init_for_tests();
+
+        assert_ne!(encrypt(b"osu! tokens", b"user 1"), encrypt(b"osu! tokens", b"user 1"));
+    }
+
+    #[test]
+    fn decryption_fails_for_other_associated_data() {
+        init_for_tests();
+
+        assert_eq!(decrypt(&encrypt(b"osu! tokens", b"user 1"), b"user 2"), None);
+    }
+
+    #[test]
+    fn decryption_fails_for_tampered_or_malformed_data() {
+        init_for_tests();
+
+        let mut bytes = URL_SAFE_NO_PAD.decode(encrypt(b"osu! tokens", b"user 1")).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+
+        assert_eq!(decrypt(&URL_SAFE_NO_PAD.encode(bytes), b"user 1"), None);
+        assert_eq!(decrypt("too short", b"user 1"), None);
+        assert_eq!(decrypt("not base64!", b"user 1"), None);
+    }
+}
@@ -125,7 +125,7 @@ pub fn generate_display(input: TokenStream) -> TokenStream {
     });
     
     let expanded = quote! {
-        #[derive(serde::Serialize)]
+        #[derive(serde::Serialize, Clone)]
         pub struct #struct_name {
             #base_field,
             #(#fields_struct,)*